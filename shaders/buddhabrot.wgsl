@@ -0,0 +1,46 @@
+#include "common.wgsl"
+
+// Draws the orbit counts accumulated by `density.wgsl`. Each channel is
+// normalized by its highest count, and square-rooted so that the sparse outer
+// orbits still show up next to the dense center
+
+struct DensityState {
+    frame: u32;
+    samples: u32;
+    width: u32;
+    height: u32;
+};
+
+struct Counts {
+    values: array<u32>;
+};
+
+struct Peaks {
+    values: array<u32, 4>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> density: DensityState;
+
+[[group(3), binding(1)]]
+var<storage, read> counts: Counts;
+
+[[group(3), binding(2)]]
+var<storage, read> peaks: Peaks;
+
+fn brightness(index: u32, channel: u32) -> f32 {
+    var peak = max(f32(peaks.values[channel]), 1.0);
+    return sqrt(f32(counts.values[index + channel]) / peak);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var x = u32(in.position.x - camera.viewport_x);
+    var y = u32(in.position.y - camera.viewport_y);
+    if (x >= density.width || y >= density.height) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    var index = (y * density.width + x) * 3u;
+    return vec4<f32>(brightness(index, 0u), brightness(index, 1u), brightness(index, 2u), 1.0);
+}
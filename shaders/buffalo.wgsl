@@ -0,0 +1,15 @@
+#include "common.wgsl"
+
+// The Buffalo: the absolute value of both parts is taken after squaring,
+// i.e. `z -> |re(z^2)| - i|im(z^2)| + c`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var w = cmul(z, z);
+    return cadd(Complex(abs(w.re), -abs(w.im)), c);
+}
+
+fn degree() -> f32 {
+    return 2.0;
+}
+
+#include "escape_time.wgsl"
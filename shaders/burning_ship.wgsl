@@ -0,0 +1,15 @@
+#include "common.wgsl"
+
+// The Burning Ship: the Mandelbrot iteration, but with the absolute value of
+// both components taken before squaring, i.e. `z -> (|x| + i|y|)^2 + c`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var w = Complex(abs(z.re), abs(z.im));
+    return cadd(cmul(w, w), c);
+}
+
+fn degree() -> f32 {
+    return 2.0;
+}
+
+#include "escape_time.wgsl"
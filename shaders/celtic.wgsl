@@ -0,0 +1,16 @@
+#include "common.wgsl"
+
+// The Celtic Mandelbrot: the absolute value of the real part is taken after
+// squaring, i.e. `z -> |re(z^2)| + i im(z^2) + c`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var w = cmul(z, z);
+    w.re = abs(w.re);
+    return cadd(w, c);
+}
+
+fn degree() -> f32 {
+    return 2.0;
+}
+
+#include "escape_time.wgsl"
@@ -0,0 +1,117 @@
+#include "common.wgsl"
+
+// The coloring pass for escape-time fractals. The fractal's own shader only
+// runs when the view changes, writing each pixel's results (see
+// `escape_value`) to the iterations texture; this colors them every frame,
+// so coloring changes show up straight away. `coloring.mode` picks how (see
+// `ColoringMode` in `coloring.rs`), and `coloring.interior` picks how the
+// pixels that never escaped are colored (see `InteriorMode`)
+
+[[group(3), binding(0)]]
+var iterations: texture_2d<f32>;
+
+// the range of escape values in view, and the fraction of escaped pixels at
+// or below each bin of their histogram (see `histogram.wgsl`)
+struct Cumulative {
+    low: f32;
+    high: f32;
+    values: array<f32, 1024>;
+};
+
+[[group(3), binding(1)]]
+var<storage, read> cumulative: Cumulative;
+
+fn line_shade(distance: f32) -> vec4<f32> {
+    // line art: black on the boundary, fading to white `line_width` pixels
+    // away from it. since the fade is measured in pixels, this also
+    // antialiases the boundary
+    var shade = clamp(distance / coloring.line_width, 0.0, 1.0);
+    return vec4<f32>(shade, shade, shade, 1.0);
+}
+
+fn distance_color(n: f32, distance: f32) -> vec4<f32> {
+    // shaders without a distance estimate fall back to escape time
+    if (distance < 0.0) {
+        return escape_time_color(n);
+    }
+    return line_shade(distance);
+}
+
+fn trap_color(n: f32, trapped: vec2<f32>) -> vec4<f32> {
+    // the closest approach to the trap, through the palette (1 at the trap,
+    // fading towards 0 far away); or the image where the orbit landed on it.
+    // shaders that don't measure traps (or orbits that missed the image)
+    // fall back to escape time
+    if (trapped.x < 0.0) {
+        return escape_time_color(n);
+    }
+    if (trap.shape == 4u) {
+        var size = vec2<f32>(textureDimensions(trap_image));
+        return textureLoad(trap_image, vec2<i32>(trapped * size), 0);
+    }
+    return palette_color(exp(-trapped.x));
+}
+
+fn average_color(n: f32, average: f32) -> vec4<f32> {
+    // the averaging colorings' smoothed mean, through the palette. shaders
+    // that don't measure it fall back to escape time
+    if (average < 0.0) {
+        return escape_time_color(n);
+    }
+    return palette_color(average);
+}
+
+fn histogram_color(n: f32) -> vec4<f32> {
+    // the fraction of escaped pixels in view that escaped no later than this
+    // one, through the palette, so each color covers about as much of the
+    // view as any other. this is interpolated within the bin, so there are no
+    // bands
+    var x = histogram_position(n, cumulative.low, cumulative.high);
+    var bin = min(u32(x), HISTOGRAM_BINS - 1u);
+    var before = 0.0;
+    if (bin > 0u) {
+        before = cumulative.values[bin - 1u];
+    }
+    return palette_color(mix(before, cumulative.values[bin], clamp(x - f32(bin), 0.0, 1.0)));
+}
+
+fn interior_color(value: vec4<f32>) -> vec4<f32> {
+    // by period, spreading neighbouring periods out along the palette with
+    // the golden ratio; by the final `|z|`, which is at most 2 for the
+    // Mandelbrot set; or by the interior distance estimate. pixels without
+    // the information needed are black. the distance can come out as NaN
+    // when the cycle couldn't be pinned down, which fails the check too
+    var period = value.a;
+    var distance = value.b;
+    if (coloring.interior == 1u && period > 0.0) {
+        return palette_color(period * 0.618034);
+    }
+    if (coloring.interior == 2u) {
+        return palette_color(value.g / 2.0);
+    }
+    if (coloring.interior == 3u && distance >= 0.0) {
+        return line_shade(distance);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var value = textureLoad(iterations, vec2<i32>(in.position.xy), 0);
+    if (value.r < 0.0) {
+        return interior_color(value);
+    }
+    if (coloring.mode == 1u) {
+        return distance_color(value.r, value.b);
+    }
+    if (coloring.mode == 2u) {
+        return trap_color(value.r, value.ba);
+    }
+    if (coloring.mode >= 3u && coloring.mode <= 5u) {
+        return average_color(value.r, value.b);
+    }
+    if (coloring.mode == 6u) {
+        return histogram_color(value.r);
+    }
+    return escape_time_color(value.r);
+}
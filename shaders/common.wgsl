@@ -0,0 +1,271 @@
+#include "complex.wgsl"
+
+struct VertexInput {
+    [[location(0), interpolate(flat)]] position: vec3<f32>;
+    [[location(1)]] color: vec3<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] color: vec3<f32>;
+};
+
+struct CameraState {
+    origin: SplitComplex;
+    julia_c: SplitComplex;
+    width: f32;
+    height: f32;
+    pixel_size: f32;
+    zoom: f32;
+    rotation_cos: f32;
+    rotation_sin: f32;
+    viewport_x: f32;
+    viewport_y: f32;
+    // the iteration limit, which grows with the zoom
+    max_iterations: u32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> camera: CameraState;
+
+// the active fractal's parameters (see `fractal.rs`), in the order the
+// fractal lists them. use `parameter(i)` to read them
+struct Parameters {
+    values: array<vec4<f32>, 4>;
+};
+
+[[group(1), binding(0)]]
+var<uniform> parameters: Parameters;
+
+// coloring settings shared by every fractal (see `coloring.rs`)
+struct Coloring {
+    bailout: f32;
+    palette_offset: f32;
+    palette_repeat: f32;
+    palette_mirror: u32;
+    mode: u32;
+    line_width: f32;
+    interior: u32;
+    stripe_density: f32;
+};
+
+[[group(2), binding(0)]]
+var<uniform> coloring: Coloring;
+
+// the palette's colors, evenly spaced. use `palette_color(t)` to look them up
+[[group(2), binding(1)]]
+var palette: texture_1d<f32>;
+
+// the orbit trap (see `trap.rs`). `orbit.wgsl` measures orbits against it
+struct Trap {
+    shape: u32;
+    size: f32;
+    center: Complex;
+    rotation_cos: f32;
+    rotation_sin: f32;
+};
+
+[[group(2), binding(2)]]
+var<uniform> trap: Trap;
+
+[[group(2), binding(3)]]
+var trap_image: texture_2d<f32>;
+
+
+[[stage(vertex)]]
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+    out.position = vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+
+fn pixel_to_delta(x: f32, y: f32) -> Complex {
+    // offset from the viewport center (i.e. `camera.origin`), on the complex
+    // plane. this stays precise at any zoom level, unlike absolute coordinates.
+    // `x` and `y` are window coordinates, which only match the viewport's when
+    // it fills the window
+    var dx = (x - camera.viewport_x - camera.width / 2.0) * camera.pixel_size;
+    var dy = (y - camera.viewport_y - camera.height / 2.0) * camera.pixel_size;
+    var res: Complex;
+    res.re = dx * camera.rotation_cos - dy * camera.rotation_sin;
+    res.im = dx * camera.rotation_sin + dy * camera.rotation_cos;
+    return res;
+}
+
+fn pixel_to_point(x: f32, y: f32) -> Complex {
+    var delta = pixel_to_delta(x, y);
+    var res: Complex;
+    res.re = camera.origin.re_hi + (camera.origin.re_lo + delta.re);
+    res.im = camera.origin.im_hi + (camera.origin.im_lo + delta.im);
+    return res;
+}
+
+fn parameter(index: i32) -> f32 {
+    return parameters.values[index / 4][index % 4];
+}
+
+fn julia_c() -> Complex {
+    return Complex(camera.julia_c.re_hi + camera.julia_c.re_lo, camera.julia_c.im_hi + camera.julia_c.im_lo);
+}
+
+fn hsv_to_rgb(hue: f32, sat: f32, val: f32) -> vec4<f32> {
+    var C = (val / 100.0) * (sat / 100.0);
+    var X = C * (1.0 - abs((hue / 60.0) % 2.0 - 1.0));
+    var m = (val / 100.0) - C;
+
+    var r: f32;
+    var g: f32;
+    var b: f32;
+    if (0.0 <= hue && hue < 60.0) {
+        r = C;
+        g = X;
+        b = 0.0;
+    }
+    else if (60.0 <= hue && hue < 120.0) {
+        r = X;
+        g = C;
+        b = 0.0;
+    }
+    else if (120.0 <= hue && hue < 180.0) {
+        r = 0.0;
+        g = C;
+        b = X;
+    }
+    else if (180.0 <= hue && hue < 240.0) {
+        r = 0.0;
+        g = X;
+        b = C;
+    }
+    else if (240.0 <= hue && hue < 300.0) {
+        r = X;
+        g = 0.0;
+        b = C;
+    }
+    else if (300.0 <= hue && hue < 360.0) {
+        r = C;
+        g = 0.0;
+        b = X;
+    }
+
+    return vec4<f32>(r + m, g + m, b + m, 1.0);
+}
+
+fn escape_radius_sqr() -> f32 {
+    // orbits have escaped once `complex_norm_sqr(z)` is bigger than this
+    return coloring.bailout * coloring.bailout;
+}
+
+fn smooth_fraction(norm_sqr: f32, degree: f32) -> f32 {
+    // the fraction of an iteration to take off the count of an orbit that
+    // escaped to `|z|^2 = norm_sqr`, iterating a formula of `degree`. each
+    // iteration raises `|z|` to (roughly) the power `degree`, so `log|z|`
+    // grows by a factor of `degree`; this is how far `|z|` got past the
+    // bailout on that scale
+    var overshoot = log(norm_sqr) / log(escape_radius_sqr());
+    return log(overshoot) / log(degree);
+}
+
+fn settled_fraction(distance_sqr: f32, tolerance_sqr: f32) -> f32 {
+    // `smooth_fraction`, for an orbit that settled down rather than escaped:
+    // it stopped once it came within `tolerance_sqr` of where it was
+    // settling (both squared), ending up `distance_sqr` away. near a
+    // superattracting point, each iteration squares that distance, so this
+    // is how far under the tolerance it got on that scale. other points pull
+    // the orbit in more slowly, which this can only approximate, so it's
+    // kept to a single iteration
+    var overshoot = log(max(distance_sqr, 1e-30)) / log(tolerance_sqr);
+    return clamp(log(overshoot) / log(2.0), 0.0, 1.0);
+}
+
+fn smooth_iterations(n: f32, norm_sqr: f32, degree: f32) -> f32 {
+    // the normalized iteration count, for an orbit that escaped to
+    // `|z|^2 = norm_sqr` after `n` iterations of a formula of `degree`. the
+    // result is continuous, so there are no bands between iteration counts
+    return n - smooth_fraction(norm_sqr, degree);
+}
+
+fn palette_color(t: f32) -> vec4<f32> {
+    // the palette color at `t`, repeated `palette_repeat` times from 0 to 1,
+    // and shifted along by `palette_offset`. the texture is read directly
+    // (rather than sampled) so this works in non-uniform control flow, e.g.
+    // after an escape-time loop; so neighbouring colors are blended here
+    var u = t * coloring.palette_repeat + coloring.palette_offset;
+    if (coloring.palette_mirror != 0u) {
+        u = 1.0 - abs(1.0 - 2.0 * fract(u / 2.0));
+    }
+    else {
+        u = fract(u);
+    }
+
+    var size = textureDimensions(palette);
+    var x = u * f32(size - 1);
+    var left = clamp(i32(floor(x)), 0, size - 1);
+    var right = min(left + 1, size - 1);
+    return mix(textureLoad(palette, left, 0), textureLoad(palette, right, 0), vec4<f32>(fract(x)));
+}
+
+fn escape_time_color(n: f32) -> vec4<f32> {
+    // `n` is the fraction of the iteration limit that was left when the
+    // pixel escaped; i.e. close to 1 far outside, and negative for the
+    // interior, which is black
+    if (n < 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    return palette_color(n);
+}
+
+// bins in the histogram of escape values, for histogram coloring (see
+// `histogram.wgsl`); this has to match `Iterations::HISTOGRAM_BINS`
+let HISTOGRAM_BINS: u32 = 1024u;
+
+fn histogram_position(n: f32, low: f32, high: f32) -> f32 {
+    // where the escape value `n` falls in the histogram of the view's escape
+    // values, which run from `low` to `high`; from 0 to `HISTOGRAM_BINS`.
+    // the bins cover just the values in view, however narrow their range is
+    if (high <= low) {
+        return 0.0;
+    }
+    return clamp((n - low) / (high - low), 0.0, 1.0) * f32(HISTOGRAM_BINS);
+}
+
+fn distance_estimate(norm_sqr: f32, derivative: Complex) -> f32 {
+    // the distance from an escaped pixel to the set, in pixels, from the
+    // final `|z|^2 = norm_sqr` and the derivative of `z` with respect to the
+    // pixel. the true distance is within a factor of 2 or so either way. the
+    // derivative is tracked in pixels (i.e. multiplied by `camera.pixel_size`)
+    // rather than on the plane, so that it doesn't overflow at deep zooms
+    var norm = sqrt(norm_sqr);
+    return norm * log(norm) / sqrt(complex_norm_sqr(derivative));
+}
+
+fn escape_value(n: f32, norm_sqr: f32) -> vec4<f32> {
+    // what escape-time fractals output: rather than a color, the iteration
+    // pass writes `n` (as for `escape_time_color`) and the final `|z|` to the
+    // iterations texture, which the coloring pass (`coloring.wgsl`) then
+    // colors every frame. the last two channels are for whatever the
+    // coloring mode needs measured as well (see `escape_value_with_distance`
+    // and `orbit_value` in `orbit.wgsl`); negative means it wasn't
+    return vec4<f32>(n, sqrt(norm_sqr), -1.0, 0.0);
+}
+
+fn interior_value(norm_sqr: f32, period: f32, distance: f32) -> vec4<f32> {
+    // what escape-time fractals output for pixels that never escaped: the
+    // final `|z|`, the interior distance estimate in pixels (negative if
+    // there isn't one), and the period of the cycle the orbit fell into (0 if
+    // none was found)
+    return vec4<f32>(-1.0, sqrt(norm_sqr), distance, period);
+}
+
+fn escape_value_with_distance(n: f32, norm_sqr: f32, derivative: Complex) -> vec4<f32> {
+    // `escape_value`, plus the distance estimate, for shaders that track the
+    // derivative (see `distance_estimate`). the estimate is only written in
+    // the distance coloring mode, since other modes use that channel too
+    if (coloring.mode != 1u) {
+        return escape_value(n, norm_sqr);
+    }
+    return vec4<f32>(n, sqrt(norm_sqr), distance_estimate(norm_sqr, derivative), 0.0);
+}
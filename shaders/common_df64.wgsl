@@ -0,0 +1,12 @@
+#include "common.wgsl"
+#include "df64.wgsl"
+
+// Camera helpers for the emulated double precision shaders
+
+fn pixel_to_point_df64(x: f32, y: f32) -> Df64Complex {
+    return df64_cadd(df64_complex_split(camera.origin), df64_complex(pixel_to_delta(x, y)));
+}
+
+fn julia_c_df64() -> Df64Complex {
+    return df64_complex_split(camera.julia_c);
+}
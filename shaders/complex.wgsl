@@ -0,0 +1,64 @@
+struct Complex {
+    re: f32;
+    im: f32;
+};
+struct SplitComplex {
+    re_hi: f32;
+    re_lo: f32;
+    im_hi: f32;
+    im_lo: f32;
+};
+
+fn cmul(a: Complex, b: Complex) -> Complex {
+    var res: Complex;
+    res.re = (a.re * b.re) - (a.im * b.im);
+    res.im = (a.re * b.im) + (a.im * b.re);
+    return res;    
+}
+fn cadd(a: Complex, b: Complex) -> Complex {
+    var res: Complex;
+    res.re = a.re + b.re;
+    res.im = a.im + b.im;
+    return res;    
+}
+fn csub(a: Complex, b: Complex) -> Complex {
+    var res: Complex;
+    res.re = a.re - b.re;
+    res.im = a.im - b.im;
+    return res;
+}
+fn cdiv(a: Complex, b: Complex) -> Complex {
+    // a * conj(b) / |b|^2
+    var norm_sqr = (b.re * b.re) + (b.im * b.im);
+    var res: Complex;
+    res.re = ((a.re * b.re) + (a.im * b.im)) / norm_sqr;
+    res.im = ((a.im * b.re) - (a.re * b.im)) / norm_sqr;
+    return res;
+}
+
+fn complex_norm_sqr(c: Complex) -> f32 {
+    var res: f32;
+    res = (c.re * c.re) + (c.im * c.im);
+    return res;
+}
+
+fn cpow(z: Complex, d: Complex) -> Complex {
+    // small positive integer powers are exact with repeated multiplication.
+    // anything else goes through polar form: z^d = exp(d * log(z))
+    if (d.im == 0.0 && d.re == floor(d.re) && d.re >= 1.0 && d.re <= 64.0) {
+        var res = z;
+        for (var k: i32 = 1; k < i32(d.re); k = k + 1) {
+            res = cmul(res, z);
+        }
+        return res;
+    }
+    if (z.re == 0.0 && z.im == 0.0) {
+        return Complex(0.0, 0.0);
+    }
+
+    var log_r = 0.5 * log(complex_norm_sqr(z));
+    var theta = atan2(z.im, z.re);
+    var r = exp(d.re * log_r - d.im * theta);
+    var angle = d.re * theta + d.im * log_r;
+    return Complex(r * cos(angle), r * sin(angle));
+}
@@ -0,0 +1,231 @@
+#include "common.wgsl"
+
+// Buddhabrot density accumulation. Rather than coloring each pixel by its own
+// orbit, random points `c` are picked from the whole Mandelbrot set's area,
+// and the orbits of those that escape are traced, counting every pixel each
+// orbit passes through. The counts build up over many frames, and are drawn
+// by `buddhabrot.wgsl`
+//
+// There are three channels of counts; an orbit that escapes after `n`
+// iterations is counted in each channel whose iteration limit (parameters 0
+// to 2) is at least `n`. Giving the channels different limits shows orbits of
+// different lengths in different colors (the "Nebulabrot")
+//
+// Once zoomed in, hardly any of the points in [-2, 2]² have orbits that pass
+// through the view, so picking them independently would leave the picture
+// black for a long time. Instead each invocation keeps a Metropolis chain:
+// its current `c` always has an orbit that visits the view, and each step
+// proposes a new `c` (either a small jump from the current one, or a fresh
+// point anywhere), moving there only if that orbit visits the view too. The
+// proposals are symmetric, so the chain samples uniformly from the points
+// that contribute to the view, which gives the same picture as uniform
+// sampling, only much sooner. The chains start over whenever the counts do
+
+struct DensityState {
+    frame: u32;
+    samples: u32;
+    width: u32;
+    height: u32;
+};
+
+// three per pixel, in rows
+struct Counts {
+    values: array<atomic<u32>>;
+};
+
+// the highest count in each channel, to normalize by
+struct Peaks {
+    values: array<atomic<u32>, 4>;
+};
+
+// one per invocation of `cs_accumulate`. `iterations` is how long the orbit
+// of `c` takes to escape, or 0 if the chain hasn't found a point yet
+struct Chain {
+    c: Complex;
+    iterations: u32;
+    _padding: u32;
+};
+
+struct Chains {
+    values: array<Chain>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> density: DensityState;
+
+[[group(3), binding(1)]]
+var<storage, read_write> counts: Counts;
+
+[[group(3), binding(2)]]
+var<storage, read_write> peaks: Peaks;
+
+[[group(3), binding(3)]]
+var<storage, read_write> chains: Chains;
+
+var<private> seed: u32;
+
+fn hash(x: u32) -> u32 {
+    // PCG; see "Hash Functions for GPU Rendering" (Jarzynski and Olano)
+    var state = x * 747796405u + 2891336453u;
+    var word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random() -> f32 {
+    // a uniformly distributed number in [0, 1)
+    seed = hash(seed);
+    return f32(seed >> 8u) / 16777216.0;
+}
+
+fn channel_limit(channel: u32) -> u32 {
+    return u32(max(parameter(i32(channel)), 1.0));
+}
+
+fn in_main_bulbs(c: Complex) -> bool {
+    // points in the main cardioid or the period-2 bulb never escape, so
+    // there's no point iterating them
+    var x = c.re - 0.25;
+    var q = x * x + c.im * c.im;
+    if (q * (q + x) <= 0.25 * c.im * c.im) {
+        return true;
+    }
+    return (c.re + 1.0) * (c.re + 1.0) + c.im * c.im <= 0.0625;
+}
+
+fn point_to_pixel(z: Complex) -> vec2<f32> {
+    // the inverse of `pixel_to_point`, relative to the viewport
+    var delta = Complex(
+        z.re - (camera.origin.re_hi + camera.origin.re_lo),
+        z.im - (camera.origin.im_hi + camera.origin.im_lo),
+    );
+    return vec2<f32>(
+        (delta.re * camera.rotation_cos + delta.im * camera.rotation_sin) / camera.pixel_size + camera.width / 2.0,
+        (delta.im * camera.rotation_cos - delta.re * camera.rotation_sin) / camera.pixel_size + camera.height / 2.0,
+    );
+}
+
+fn in_view(pixel: vec2<f32>) -> bool {
+    return pixel.x >= 0.0 && pixel.y >= 0.0 && pixel.x < f32(density.width) && pixel.y < f32(density.height);
+}
+
+fn deposit(z: Complex, iterations: u32) {
+    // count @z in every channel that an orbit of @iterations belongs to
+    var pixel = point_to_pixel(z);
+    if (!in_view(pixel)) {
+        return;
+    }
+
+    var index = (u32(pixel.y) * density.width + u32(pixel.x)) * 3u;
+    for (var channel: u32 = 0u; channel < 3u; channel = channel + 1u) {
+        if (iterations <= channel_limit(channel)) {
+            var count = atomicAdd(&counts.values[index + channel], 1u) + 1u;
+            atomicMax(&peaks.values[channel], count);
+        }
+    }
+}
+
+fn escape_iterations(c: Complex, max_iterations: u32) -> u32 {
+    // how many iterations the orbit of @c takes to escape, or 0 if it
+    // doesn't, or never passes through the view on the way
+    if (c.re < -2.0 || c.re > 2.0 || c.im < -2.0 || c.im > 2.0 || in_main_bulbs(c)) {
+        return 0u;
+    }
+
+    var z = Complex(0.0, 0.0);
+    var n: u32 = 0u;
+    loop {
+        if (n >= max_iterations || complex_norm_sqr(z) > 4.0) {
+            break;
+        }
+        z = cadd(cmul(z, z), c);
+        n = n + 1u;
+    }
+    if (complex_norm_sqr(z) <= 4.0) {
+        return 0u;
+    }
+
+    z = Complex(0.0, 0.0);
+    for (var i: u32 = 0u; i < n; i = i + 1u) {
+        z = cadd(cmul(z, z), c);
+        if (in_view(point_to_pixel(z))) {
+            return n;
+        }
+    }
+    return 0u;
+}
+
+fn propose(chain: Chain) -> Complex {
+    // the next point for @chain to try. Until it has found one, points in
+    // the view itself are the likeliest to work, since the first step of
+    // every orbit is `c`. After that, a quarter of the proposals are
+    // anywhere, so the chain can't get stuck, and the rest are jumps from
+    // the current point, of anything from a thousandth of the view's size
+    // to all of it
+    if (chain.iterations == 0u) {
+        if (random() < 0.5) {
+            return pixel_to_point(
+                camera.viewport_x + random() * camera.width,
+                camera.viewport_y + random() * camera.height,
+            );
+        }
+        return Complex(random() * 4.0 - 2.0, random() * 4.0 - 2.0);
+    }
+    if (random() < 0.25) {
+        return Complex(random() * 4.0 - 2.0, random() * 4.0 - 2.0);
+    }
+
+    var view_size = camera.pixel_size * max(camera.width, camera.height);
+    var distance = view_size * exp2(-10.0 * random());
+    var angle = 6.28318530718 * random();
+    return Complex(chain.c.re + distance * cos(angle), chain.c.im + distance * sin(angle));
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn cs_accumulate([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    if (id.x >= arrayLength(&chains.values)) {
+        return;
+    }
+
+    var max_iterations = max(channel_limit(0u), max(channel_limit(1u), channel_limit(2u)));
+    var chain = chains.values[id.x];
+    seed = hash(id.x ^ hash(density.frame));
+
+    for (var sample: u32 = 0u; sample < density.samples; sample = sample + 1u) {
+        // move if the proposal's orbit visits the view...
+        var c = propose(chain);
+        var n = escape_iterations(c, max_iterations);
+        if (n > 0u) {
+            chain.c = c;
+            chain.iterations = n;
+        }
+        if (chain.iterations == 0u) {
+            continue;
+        }
+
+        // ...then count every point of the current orbit, whether it moved
+        // or not
+        var z = Complex(0.0, 0.0);
+        for (var i: u32 = 0u; i < chain.iterations; i = i + 1u) {
+            z = cadd(cmul(z, z), chain.c);
+            deposit(z, chain.iterations);
+        }
+    }
+    chains.values[id.x] = chain;
+}
+
+[[stage(compute), workgroup_size(256)]]
+fn cs_clear(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(num_workgroups)]] workgroups: vec3<u32>,
+) {
+    var stride = workgroups.x * 256u;
+    for (var i = id.x; i < arrayLength(&counts.values); i = i + stride) {
+        atomicStore(&counts.values[i], 0u);
+    }
+    if (id.x < 4u) {
+        atomicStore(&peaks.values[id.x], 0u);
+    }
+    for (var i = id.x; i < arrayLength(&chains.values); i = i + stride) {
+        chains.values[i].iterations = 0u;
+    }
+}
@@ -0,0 +1,150 @@
+#include "complex.wgsl"
+
+// Emulated double precision ("double-float") arithmetic. Each number is an
+// unevaluated sum of two f32s, `hi + lo`, where `lo` holds the rounding error
+// of `hi`. This gives ~48 bits of mantissa, versus 24 for a plain f32, without
+// needing f64 support from the GPU
+//
+// See: Dekker (1971), and Thall, "Extended-Precision Floating-Point Numbers
+// for GPU Computation" (2006)
+//
+// Note that all of this relies on the GPU *not* re-associating float math;
+// e.g. `(a + b) - a` must not be simplified to `b`
+
+struct Df64 {
+    hi: f32;
+    lo: f32;
+};
+
+struct Df64Complex {
+    re: Df64;
+    im: Df64;
+};
+
+fn df64(a: f32) -> Df64 {
+    var res: Df64;
+    res.hi = a;
+    res.lo = 0.0;
+    return res;
+}
+
+fn df64_quick_two_sum(a: f32, b: f32) -> Df64 {
+    // a + b, assuming |a| >= |b|
+    var res: Df64;
+    res.hi = a + b;
+    res.lo = b - (res.hi - a);
+    return res;
+}
+
+fn df64_two_sum(a: f32, b: f32) -> Df64 {
+    var res: Df64;
+    res.hi = a + b;
+    var v = res.hi - a;
+    res.lo = (a - (res.hi - v)) + (b - v);
+    return res;
+}
+
+fn df64_split(a: f32) -> Df64 {
+    // split @a into two 12-bit halves, so their products are exact in f32
+    var t = 4097.0 * a;
+    var res: Df64;
+    res.hi = t - (t - a);
+    res.lo = a - res.hi;
+    return res;
+}
+
+fn df64_two_prod(a: f32, b: f32) -> Df64 {
+    var sa = df64_split(a);
+    var sb = df64_split(b);
+    var res: Df64;
+    res.hi = a * b;
+    res.lo = ((sa.hi * sb.hi - res.hi) + sa.hi * sb.lo + sa.lo * sb.hi) + sa.lo * sb.lo;
+    return res;
+}
+
+fn df64_add(a: Df64, b: Df64) -> Df64 {
+    var s = df64_two_sum(a.hi, b.hi);
+    var t = df64_two_sum(a.lo, b.lo);
+    s.lo = s.lo + t.hi;
+    s = df64_quick_two_sum(s.hi, s.lo);
+    s.lo = s.lo + t.lo;
+    return df64_quick_two_sum(s.hi, s.lo);
+}
+
+fn df64_neg(a: Df64) -> Df64 {
+    var res: Df64;
+    res.hi = -a.hi;
+    res.lo = -a.lo;
+    return res;
+}
+
+fn df64_sub(a: Df64, b: Df64) -> Df64 {
+    return df64_add(a, df64_neg(b));
+}
+
+fn df64_mul(a: Df64, b: Df64) -> Df64 {
+    var p = df64_two_prod(a.hi, b.hi);
+    p.lo = p.lo + (a.hi * b.lo + a.lo * b.hi);
+    return df64_quick_two_sum(p.hi, p.lo);
+}
+
+fn df64_mul_f32(a: Df64, b: f32) -> Df64 {
+    var p = df64_two_prod(a.hi, b);
+    p.lo = p.lo + a.lo * b;
+    return df64_quick_two_sum(p.hi, p.lo);
+}
+
+fn df64_complex(c: Complex) -> Df64Complex {
+    var res: Df64Complex;
+    res.re = df64(c.re);
+    res.im = df64(c.im);
+    return res;
+}
+
+fn df64_complex_split(c: SplitComplex) -> Df64Complex {
+    var res: Df64Complex;
+    res.re = df64_two_sum(c.re_hi, c.re_lo);
+    res.im = df64_two_sum(c.im_hi, c.im_lo);
+    return res;
+}
+
+fn df64_cadd(a: Df64Complex, b: Df64Complex) -> Df64Complex {
+    var res: Df64Complex;
+    res.re = df64_add(a.re, b.re);
+    res.im = df64_add(a.im, b.im);
+    return res;
+}
+
+fn df64_csub(a: Df64Complex, b: Df64Complex) -> Df64Complex {
+    var res: Df64Complex;
+    res.re = df64_sub(a.re, b.re);
+    res.im = df64_sub(a.im, b.im);
+    return res;
+}
+
+fn df64_cmul(a: Df64Complex, b: Df64Complex) -> Df64Complex {
+    var res: Df64Complex;
+    res.re = df64_sub(df64_mul(a.re, b.re), df64_mul(a.im, b.im));
+    res.im = df64_add(df64_mul(a.re, b.im), df64_mul(a.im, b.re));
+    return res;
+}
+
+fn df64_csqr(a: Df64Complex) -> Df64Complex {
+    // slightly cheaper than `df64_cmul(a, a)`
+    var res: Df64Complex;
+    res.re = df64_sub(df64_mul(a.re, a.re), df64_mul(a.im, a.im));
+    res.im = df64_mul_f32(df64_mul(a.re, a.im), 2.0);
+    return res;
+}
+
+fn df64_complex_norm_sqr(c: Df64Complex) -> f32 {
+    // only used for bailout checks, so the low parts are not needed
+    return (c.re.hi * c.re.hi) + (c.im.hi * c.im.hi);
+}
+
+fn df64_complex_to_f32(c: Df64Complex) -> Complex {
+    var res: Complex;
+    res.re = c.re.hi;
+    res.im = c.im.hi;
+    return res;
+}
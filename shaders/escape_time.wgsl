@@ -0,0 +1,43 @@
+#include "orbit.wgsl"
+
+// The escape-time loop shared by the Mandelbrot set's `abs`-based relatives
+// (Burning Ship, Tricorn, ...). Each of those defines its iteration as
+// `fn formula(z: Complex, c: Complex) -> Complex`, and the power it (roughly)
+// raises `z` to as `fn degree() -> f32` (for `smooth_iterations`), then
+// includes this file, which provides the entry points for both planes:
+//
+//   fs_main:  the parameter plane; `z` starts at 0, and `c` is the pixel
+//   fs_julia: the Julia sets; `z` starts at the pixel, and `c` is fixed
+
+fn escape_time(z0: Complex, c: Complex) -> vec4<f32> {
+    var z = z0;
+    var orbit = orbit_start(z);
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0 || complex_norm_sqr(z) > escape_radius_sqr()) {
+            break;
+        }
+        z = formula(z, c);
+        i = i - 1.0;
+        orbit = orbit_step(orbit, z, c);
+        if (orbit.cycle.period > 0.0) {
+            return interior_value(complex_norm_sqr(z), orbit.cycle.period, -1.0);
+        }
+    }
+    if (i < 0.0) {
+        return interior_value(complex_norm_sqr(z), 0.0, -1.0);
+    }
+    var fraction = smooth_fraction(complex_norm_sqr(z), degree());
+    return orbit_value(orbit, 1.0 - (max - i - fraction) / max, complex_norm_sqr(z), fraction);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return escape_time(Complex(0.0, 0.0), pixel_to_point(in.position.x, in.position.y));
+}
+
+[[stage(fragment)]]
+fn fs_julia(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return escape_time(pixel_to_point(in.position.x, in.position.y), julia_c());
+}
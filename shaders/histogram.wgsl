@@ -0,0 +1,140 @@
+#include "common.wgsl"
+
+// Histogram equalization, for the histogram coloring mode. With the escape
+// values (see `escape_value`) mapped straight onto the palette, a deep zoom
+// with a high iteration limit only covers a sliver of it, so comes out as one
+// flat color. Instead, this builds a histogram of the escape values in view
+// after each iteration pass, and adds it up, so the coloring pass can color
+// each pixel by the fraction of pixels that escaped no later than it did.
+// That spreads the colors evenly over the view at any depth
+//
+// The passes, in order:
+//
+//   cs_clear:  empty the histogram
+//   cs_range:  find the range of escape values in view, which the bins span
+//   cs_count:  count the pixels in each bin
+//   cs_prefix: add up the counts (a prefix sum), as fractions of the total
+
+[[group(3), binding(0)]]
+var iterations: texture_2d<f32>;
+
+// the cumulative histogram, as read by `coloring.wgsl`
+struct Cumulative {
+    low: f32;
+    high: f32;
+    values: array<f32, 1024>;
+};
+
+[[group(3), binding(1)]]
+var<storage, read_write> cumulative: Cumulative;
+
+// the range of escape values, as the bits of the (positive) floats, which
+// compare the same way as the floats themselves; then the counts
+struct Histogram {
+    low: atomic<u32>;
+    high: atomic<u32>;
+    bins: array<atomic<u32>, 1024>;
+};
+
+[[group(3), binding(2)]]
+var<storage, read_write> histogram: Histogram;
+
+// pixels to leave out, because the Julia preview covers them
+struct Mask {
+    x: u32;
+    y: u32;
+    width: u32;
+    height: u32;
+};
+
+[[group(3), binding(3)]]
+var<uniform> mask: Mask;
+
+// each invocation of `cs_prefix` adds up a run of this many bins
+let BINS_PER_INVOCATION: u32 = 4u;
+
+var<workgroup> totals: array<u32, 256>;
+
+fn escaped_value(id: vec3<u32>) -> f32 {
+    // the escape value of the pixel at `id`, or -1 if it's outside the
+    // texture, under the mask, or never escaped
+    var size = textureDimensions(iterations);
+    if (i32(id.x) >= size.x || i32(id.y) >= size.y) {
+        return -1.0;
+    }
+    // (left of or above the mask, the subtraction wraps around)
+    if (id.x - mask.x < mask.width && id.y - mask.y < mask.height) {
+        return -1.0;
+    }
+    return textureLoad(iterations, vec2<i32>(id.xy), 0).r;
+}
+
+[[stage(compute), workgroup_size(256)]]
+fn cs_clear([[builtin(local_invocation_index)]] index: u32) {
+    for (var i: u32 = index; i < HISTOGRAM_BINS; i = i + 256u) {
+        atomicStore(&histogram.bins[i], 0u);
+    }
+    if (index == 0u) {
+        atomicStore(&histogram.low, 0xffffffffu);
+        atomicStore(&histogram.high, 0u);
+    }
+}
+
+[[stage(compute), workgroup_size(8, 8)]]
+fn cs_range([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    var n = escaped_value(id);
+    if (n < 0.0) {
+        return;
+    }
+    atomicMin(&histogram.low, bitcast<u32>(n));
+    atomicMax(&histogram.high, bitcast<u32>(n));
+}
+
+[[stage(compute), workgroup_size(8, 8)]]
+fn cs_count([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    var n = escaped_value(id);
+    if (n < 0.0) {
+        return;
+    }
+    var low = bitcast<f32>(atomicLoad(&histogram.low));
+    var high = bitcast<f32>(atomicLoad(&histogram.high));
+    var bin = min(u32(histogram_position(n, low, high)), HISTOGRAM_BINS - 1u);
+    atomicAdd(&histogram.bins[bin], 1u);
+}
+
+[[stage(compute), workgroup_size(256)]]
+fn cs_prefix([[builtin(local_invocation_index)]] index: u32) {
+    // a single workgroup; each invocation adds up its own run of bins...
+    var start = index * BINS_PER_INVOCATION;
+    var sum: u32 = 0u;
+    for (var i: u32 = 0u; i < BINS_PER_INVOCATION; i = i + 1u) {
+        sum = sum + atomicLoad(&histogram.bins[start + i]);
+    }
+    totals[index] = sum;
+    workgroupBarrier();
+
+    // ...then the runs are added up across the workgroup, doubling the
+    // distance each round (Hillis and Steele), so that each invocation ends
+    // up with the total up to the end of its run...
+    for (var offset: u32 = 1u; offset < 256u; offset = offset * 2u) {
+        var total = totals[index];
+        if (index >= offset) {
+            total = total + totals[index - offset];
+        }
+        workgroupBarrier();
+        totals[index] = total;
+        workgroupBarrier();
+    }
+
+    // ...and fills in its own bins from the total before them
+    var pixels = f32(max(totals[255], 1u));
+    var running = totals[index] - sum;
+    for (var i: u32 = 0u; i < BINS_PER_INVOCATION; i = i + 1u) {
+        running = running + atomicLoad(&histogram.bins[start + i]);
+        cumulative.values[start + i] = f32(running) / pixels;
+    }
+    if (index == 0u) {
+        cumulative.low = bitcast<f32>(atomicLoad(&histogram.low));
+        cumulative.high = bitcast<f32>(atomicLoad(&histogram.high));
+    }
+}
@@ -0,0 +1,11 @@
+#include "quadratic.wgsl"
+
+// The Julia set for `camera.julia_c`. This is the same iteration as
+// `mandelbrot.wgsl`, but `c` is fixed, and each pixel is the starting `z`
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    // the derivative is with respect to the starting point, which moves a
+    // pixel per pixel
+    return quadratic(pixel_to_point(in.position.x, in.position.y), julia_c(), Complex(camera.pixel_size, 0.0), false);
+}
@@ -0,0 +1,37 @@
+#include "orbit_df64.wgsl"
+
+// The same as `julia.wgsl`, but iterating in emulated double precision
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var c = julia_c_df64();
+    var z = pixel_to_point_df64(in.position.x, in.position.y);
+    // the derivative of `z` with respect to its starting point, in pixels.
+    // this only needs to be roughly right, so plain f32 will do
+    var derivative = Complex(camera.pixel_size, 0.0);
+    var orbit = orbit_df64_start(z);
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0 || df64_complex_norm_sqr(z) > escape_radius_sqr()) {
+            break;
+        }
+        var z_f32 = df64_complex_to_f32(z);
+        derivative = cmul(cadd(z_f32, z_f32), derivative);
+        z = df64_cadd(df64_csqr(z), c);
+        i = i - 1.0;
+        orbit = orbit_df64_step(orbit, z, c);
+        if (orbit.orbit.cycle.period > 0.0) {
+            return interior_value(df64_complex_norm_sqr(z), orbit.orbit.cycle.period, -1.0);
+        }
+    }
+    if (i < 0.0) {
+        return interior_value(df64_complex_norm_sqr(z), 0.0, -1.0);
+    }
+    var fraction = smooth_fraction(df64_complex_norm_sqr(z), 2.0);
+    var n = 1.0 - (max - i - fraction) / max;
+    if (coloring.mode == 1u) {
+        return escape_value_with_distance(n, df64_complex_norm_sqr(z), derivative);
+    }
+    return orbit_value(orbit.orbit, n, df64_complex_norm_sqr(z), fraction);
+}
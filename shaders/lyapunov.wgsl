@@ -0,0 +1,64 @@
+#include "common.wgsl"
+
+// Lyapunov fractals. Each pixel is a pair of growth rates `(a, b)` (the real
+// and imaginary parts of its point), which drive the logistic map
+//
+//   x_{n+1} = r_n * x_n * (1 - x_n)
+//
+// where `r_n` is `a` or `b`, following the repeating A/B sequence from
+// `sequence.rs`. The pixel is colored by the map's Lyapunov exponent:
+//
+//   lambda = 1/N * sum(log|r_n * (1 - 2 * x_n)|)
+//
+// Negative exponents (yellow) mean the map settles into a stable cycle, and
+// positive ones (blue) mean it's chaotic
+
+struct SequenceState {
+    length: u32;
+};
+
+struct Steps {
+    values: array<u32>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> sequence: SequenceState;
+
+// 0 for `A`, 1 for `B`
+[[group(3), binding(1)]]
+var<storage, read> steps: Steps;
+
+fn growth_rate(r: Complex, n: u32) -> f32 {
+    return select(r.re, r.im, steps.values[n % sequence.length] == 1u);
+}
+
+fn lyapunov_exponent(r: Complex) -> f32 {
+    var warmup: u32 = 100u;
+    var iterations: u32 = 400u;
+
+    // let the orbit settle before measuring it
+    var x: f32 = 0.5;
+    for (var n: u32 = 0u; n < warmup; n = n + 1u) {
+        var rn = growth_rate(r, n);
+        x = rn * x * (1.0 - x);
+    }
+
+    var sum: f32 = 0.0;
+    for (var n: u32 = warmup; n < warmup + iterations; n = n + 1u) {
+        var rn = growth_rate(r, n);
+        sum = sum + log(abs(rn * (1.0 - 2.0 * x)));
+        x = rn * x * (1.0 - x);
+    }
+    return sum / f32(iterations);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var exponent = lyapunov_exponent(pixel_to_point(in.position.x, in.position.y));
+    if (exponent < 0.0) {
+        var t = clamp(-exponent, 0.0, 1.0);
+        return vec4<f32>(mix(vec3<f32>(0.25, 0.15, 0.0), vec3<f32>(1.0, 0.85, 0.1), vec3<f32>(t)), 1.0);
+    }
+    var t = clamp(exponent, 0.0, 1.0);
+    return vec4<f32>(mix(vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(0.1, 0.3, 1.0), vec3<f32>(t)), 1.0);
+}
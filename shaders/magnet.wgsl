@@ -0,0 +1,51 @@
+#include "orbit.wgsl"
+
+// The loop shared by the Magnet fractals, which come from models of magnetic
+// phase transitions. Each defines its iteration as
+// `fn formula(z: Complex, c: Complex) -> Complex`, then includes this file.
+//
+// Besides escaping to infinity, orbits can settle on the fixed point `z = 1`;
+// either way, pixels are colored by how long that takes. Orbits that do
+// neither are the interior. Since settling on a point colors a pixel rather
+// than putting it inside the set, cycles aren't looked for; only traps and
+// averages are measured along the way
+
+// orbits have settled on `z = 1` once they're this close (squared)
+let MAGNET_TOLERANCE: f32 = 0.000001;
+
+fn magnet(z0: Complex, c: Complex) -> vec4<f32> {
+    var z = z0;
+    var orbit = orbit_start(z);
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0 || complex_norm_sqr(z) > escape_radius_sqr() || complex_norm_sqr(csub(z, Complex(1.0, 0.0))) < MAGNET_TOLERANCE) {
+            break;
+        }
+        z = formula(z, c);
+        i = i - 1.0;
+        orbit = orbit_measure(orbit, z, c);
+    }
+    if (i < 0.0) {
+        return interior_value(complex_norm_sqr(z), 0.0, -1.0);
+    }
+    // both formulas grow like `z^2` far out, and `z = 1` is superattracting
+    var fraction: f32 = 0.0;
+    if (complex_norm_sqr(z) > escape_radius_sqr()) {
+        fraction = smooth_fraction(complex_norm_sqr(z), 2.0);
+    }
+    else {
+        fraction = settled_fraction(complex_norm_sqr(csub(z, Complex(1.0, 0.0))), MAGNET_TOLERANCE);
+    }
+    return orbit_value(orbit, 1.0 - (max - i - fraction) / max, complex_norm_sqr(z), fraction);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return magnet(Complex(0.0, 0.0), pixel_to_point(in.position.x, in.position.y));
+}
+
+[[stage(fragment)]]
+fn fs_julia(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return magnet(pixel_to_point(in.position.x, in.position.y), julia_c());
+}
@@ -0,0 +1,12 @@
+#include "common.wgsl"
+
+// Magnet type I: `z -> ((z^2 + c - 1) / (2z + c - 2))^2`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var one = Complex(1.0, 0.0);
+    var two = Complex(2.0, 0.0);
+    var w = cdiv(csub(cadd(cmul(z, z), c), one), csub(cadd(cmul(two, z), c), two));
+    return cmul(w, w);
+}
+
+#include "magnet.wgsl"
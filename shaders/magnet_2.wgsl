@@ -0,0 +1,19 @@
+#include "common.wgsl"
+
+// Magnet type II:
+//
+//   z -> ((z^3 + 3(c - 1)z + (c - 1)(c - 2)) / (3z^2 + 3(c - 2)z + (c - 1)(c - 2) + 1))^2
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var three = Complex(3.0, 0.0);
+    var c1 = csub(c, Complex(1.0, 0.0));
+    var c2 = csub(c, Complex(2.0, 0.0));
+    var c1c2 = cmul(c1, c2);
+    var z2 = cmul(z, z);
+    var numerator = cadd(cadd(cmul(z2, z), cmul(cmul(three, c1), z)), c1c2);
+    var denominator = cadd(cadd(cadd(cmul(three, z2), cmul(cmul(three, c2), z)), c1c2), Complex(1.0, 0.0));
+    var w = cdiv(numerator, denominator);
+    return cmul(w, w);
+}
+
+#include "magnet.wgsl"
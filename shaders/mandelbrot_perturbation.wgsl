@@ -0,0 +1,128 @@
+#include "orbit.wgsl"
+
+// Perturbation rendering for deep zooms. A single high-precision reference
+// orbit `Z_n` is calculated on the CPU (see `reference.rs`), and each pixel
+// only iterates its offset `dz_n` from that orbit:
+//
+//   dz_{n+1} = 2 * Z_n * dz_n + dz_n^2 + dc
+//
+// where `dc` is the pixel's offset from the reference point. These offsets
+// are tiny, but f32 has plenty of *relative* precision to hold them
+//
+// The first `series_skip` iterations are skipped entirely, by evaluating a
+// polynomial in `dc` (see `series.rs`)
+
+struct ReferenceState {
+    offset: Complex;
+    length: u32;
+    max_iterations: u32;
+    series_skip: u32;
+    series_terms: u32;
+    series_radius: f32;
+};
+
+struct Orbit {
+    points: array<Complex>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> reference: ReferenceState;
+
+[[group(3), binding(1)]]
+var<storage, read> reference_orbit: Orbit;
+
+[[group(3), binding(2)]]
+var<storage, read> series: Orbit;
+
+fn series_approximation(dc: Complex) -> Complex {
+    // the coefficients are pre-scaled by `series_radius`, so evaluate them
+    // against `dc / series_radius` (horner's method)
+    var u = dc;
+    u.re = u.re / reference.series_radius;
+    u.im = u.im / reference.series_radius;
+
+    var res = Complex(0.0, 0.0);
+    for (var k: u32 = reference.series_terms; k > 0u; k = k - 1u) {
+        res = cmul(cadd(res, series.points[k - 1u]), u);
+    }
+    return res;
+}
+
+fn series_derivative(dc: Complex) -> Complex {
+    // the derivative of `series_approximation` with respect to `dc`, term by
+    // term, in pixels (see `distance_estimate`)
+    var u = dc;
+    u.re = u.re / reference.series_radius;
+    u.im = u.im / reference.series_radius;
+
+    var res = Complex(0.0, 0.0);
+    for (var k: u32 = reference.series_terms; k > 0u; k = k - 1u) {
+        var term = series.points[k - 1u];
+        res = cadd(cmul(res, u), Complex(f32(k) * term.re, f32(k) * term.im));
+    }
+    var scale = camera.pixel_size / reference.series_radius;
+    return Complex(res.re * scale, res.im * scale);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var dc = cadd(reference.offset, pixel_to_delta(in.position.x, in.position.y));
+    // traps and averages only need `z` and `c` roughly, so f32 will do. the
+    // iterations skipped by the series approximation aren't measured
+    var c = pixel_to_point(in.position.x, in.position.y);
+    var dz = series_approximation(dc);
+    // the derivative of the full `z` with respect to `dc`, in pixels
+    var derivative = series_derivative(dc);
+    var m: u32 = reference.series_skip;
+    var i: u32 = reference.series_skip;
+    var norm_sqr: f32 = 0.0;
+    var orbit = orbit_start(cadd(reference_orbit.points[m], dz));
+    loop {
+        if (i >= reference.max_iterations) {
+            break;
+        }
+
+        var z_ref = reference_orbit.points[m];
+        var z = cadd(z_ref, dz);
+        norm_sqr = complex_norm_sqr(z);
+        if (i > reference.series_skip) {
+            orbit = orbit_measure(orbit, z, c);
+        }
+        if (norm_sqr > escape_radius_sqr()) {
+            break;
+        }
+
+        // glitch detection & rebasing: once the full value `z` gets closer to
+        // zero than the offset `dz`, the offset has lost the precision it
+        // needs (a "glitch"). the same happens when the reference orbit runs
+        // out (the reference escaped). either way, restart from the beginning
+        // of the reference orbit, with `z` as the new offset
+        if (complex_norm_sqr(z) < complex_norm_sqr(dz) || m + 1u >= reference.length) {
+            dz = z;
+            m = 0u;
+            z_ref = reference_orbit.points[0];
+        }
+
+        derivative = cadd(cmul(cadd(z, z), derivative), Complex(camera.pixel_size, 0.0));
+
+        // dz * (2 * Z + dz) + dc
+        dz = cadd(cmul(dz, cadd(cadd(z_ref, z_ref), dz)), dc);
+        m = m + 1u;
+        i = i + 1u;
+    }
+
+    var max = f32(reference.max_iterations);
+    if (i >= reference.max_iterations) {
+        // there's no cycle detection here: `z` is only known to f32 precision
+        // around the reference orbit, which is far coarser than a pixel at
+        // these zooms, so nearby points can't be told from a cycle. so the
+        // interior has no period or distance; only `|z|`
+        return interior_value(norm_sqr, 0.0, -1.0);
+    }
+    var fraction = smooth_fraction(norm_sqr, 2.0);
+    var n = 1.0 - (f32(i) - fraction) / max;
+    if (coloring.mode == 1u) {
+        return escape_value_with_distance(n, norm_sqr, derivative);
+    }
+    return orbit_value(orbit, n, norm_sqr, fraction);
+}
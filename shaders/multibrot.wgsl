@@ -0,0 +1,20 @@
+#include "common.wgsl"
+
+// The Multibrot sets: `z -> z^d + c`, for any (complex) power `d`, given by
+// the `power` and `power-im` parameters. `d = 2` is the Mandelbrot set
+//
+// `d` comes from the parameters uniform rather than the camera's: each view
+// (the main view, the Julia view and the preview) has its own camera, but
+// they all share one set of parameters, which is also what
+// `--animate <name>` sweeps
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    return cadd(cpow(z, Complex(parameter(0), parameter(1))), c);
+}
+
+fn degree() -> f32 {
+    // the real part of the power decides how quickly `|z|` grows
+    return max(parameter(0), 1.1);
+}
+
+#include "escape_time.wgsl"
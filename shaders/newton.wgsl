@@ -0,0 +1,77 @@
+#include "common.wgsl"
+
+// Newton's method fractal: each pixel is a starting guess `z`, refined with
+//
+//   z -> z - a * p(z) / p'(z)
+//
+// until it settles on one of the roots of the polynomial `p`. The pixel is
+// colored by which root it reached (the hue), and how quickly (brightness).
+// `a` is the `relaxation` parameter; 1 is plain Newton's method
+//
+// The polynomial comes from `polynomial.rs`
+
+struct PolynomialState {
+    degree: u32;
+    roots: u32;
+};
+
+struct Coefficients {
+    values: array<Complex>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> polynomial: PolynomialState;
+
+// lowest order first
+[[group(3), binding(1)]]
+var<storage, read> coefficients: Coefficients;
+
+[[group(3), binding(2)]]
+var<storage, read> roots: Coefficients;
+
+fn newton_step(z: Complex, relaxation: Complex) -> Complex {
+    // evaluate p(z) and p'(z) together, with horner's method
+    var p = Complex(0.0, 0.0);
+    var dp = Complex(0.0, 0.0);
+    for (var k: i32 = i32(polynomial.degree); k >= 0; k = k - 1) {
+        dp = cadd(cmul(dp, z), p);
+        p = cadd(cmul(p, z), coefficients.values[k]);
+    }
+    return csub(z, cmul(relaxation, cdiv(p, dp)));
+}
+
+fn closest_root(z: Complex, tolerance: f32) -> i32 {
+    // the index of the root within `tolerance` of `z`, or -1 if there isn't one
+    for (var k: u32 = 0u; k < polynomial.roots; k = k + 1u) {
+        if (complex_norm_sqr(csub(z, roots.values[k])) < tolerance * tolerance) {
+            return i32(k);
+        }
+    }
+    return -1;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var relaxation = Complex(parameter(0), parameter(1));
+    var z = pixel_to_point(in.position.x, in.position.y);
+    var max: f32 = 64.0;
+    var i: f32 = 0.0;
+    var root: i32 = -1;
+    loop {
+        if (i >= max) {
+            break;
+        }
+        root = closest_root(z, 0.001);
+        if (root >= 0) {
+            break;
+        }
+        z = newton_step(z, relaxation);
+        i = i + 1.0;
+    }
+
+    if (root < 0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    var hue = f32(root) / f32(polynomial.roots) * 360.0;
+    return hsv_to_rgb(hue, 70.0, 100.0 * (1.0 - i / max));
+}
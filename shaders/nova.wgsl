@@ -0,0 +1,63 @@
+#include "orbit.wgsl"
+
+// The Nova: Newton's method for `z^d - 1`, relaxed by `a`, with `c` added on
+// at each step:
+//
+//   z -> z - a * (z^d - 1) / (d * z^(d - 1)) + c
+//
+// `d` is the `power` parameter, and `a` is `relaxation` and `relaxation-im`.
+// Pixels are colored by how long they take to settle (or escape). On the
+// parameter plane, each pixel starts at `z = 1`, a critical point of the
+// iteration
+//
+// Settling on a point is what colors a pixel here, rather than what puts it
+// inside the set, so cycles aren't looked for; only traps and averages are
+// measured along the way. Orbits that neither settle nor escape are the
+// interior
+
+// orbits have settled once a step moves `z` less than this (squared)
+let NOVA_TOLERANCE: f32 = 0.00000001;
+
+fn nova(z0: Complex, c: Complex) -> vec4<f32> {
+    var power = Complex(parameter(0), 0.0);
+    var relaxation = Complex(parameter(1), parameter(2));
+    var z = z0;
+    var orbit = orbit_start(z);
+    var moved_sqr: f32 = 0.0;
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0) {
+            break;
+        }
+        var z_power = cpow(z, Complex(power.re - 1.0, 0.0));
+        var step = cdiv(csub(cmul(z_power, z), Complex(1.0, 0.0)), cmul(power, z_power));
+        var z_next = cadd(csub(z, cmul(relaxation, step)), c);
+        moved_sqr = complex_norm_sqr(csub(z_next, z));
+        if (moved_sqr < NOVA_TOLERANCE || !(complex_norm_sqr(z_next) < 1000000000000.0)) {
+            break;
+        }
+        z = z_next;
+        i = i - 1.0;
+        orbit = orbit_measure(orbit, z, c);
+    }
+    if (i < 0.0) {
+        return interior_value(complex_norm_sqr(z), 0.0, -1.0);
+    }
+    // orbits that blew up instead just count whole iterations
+    var fraction: f32 = 0.0;
+    if (moved_sqr < NOVA_TOLERANCE) {
+        fraction = settled_fraction(moved_sqr, NOVA_TOLERANCE);
+    }
+    return orbit_value(orbit, 1.0 - (max - i - fraction) / max, complex_norm_sqr(z), fraction);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return nova(Complex(1.0, 0.0), pixel_to_point(in.position.x, in.position.y));
+}
+
+[[stage(fragment)]]
+fn fs_julia(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return nova(pixel_to_point(in.position.x, in.position.y), julia_c());
+}
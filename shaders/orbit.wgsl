@@ -0,0 +1,238 @@
+#include "common.wgsl"
+
+// Per-step hooks for escape-time loops, which measure whatever the coloring
+// needs from each orbit as it goes: cycles (for early bailout, and the
+// interior), orbit traps, and the averaging colorings. A loop calls
+// `orbit_start` with the starting `z`, then `orbit_step` after every
+// iteration, returning early once it finds a cycle; if the orbit escapes,
+// it outputs `orbit_value`:
+//
+//   var orbit = orbit_start(z);
+//   loop {
+//       if (i < 0.0 || complex_norm_sqr(z) > escape_radius_sqr()) {
+//           break;
+//       }
+//       z = formula(z, c);
+//       i = i - 1.0;
+//       orbit = orbit_step(orbit, z, c);
+//       if (orbit.cycle.period > 0.0) {
+//           return interior_value(complex_norm_sqr(z), orbit.cycle.period, -1.0);
+//       }
+//   }
+
+// Brent's cycle detection. Orbits inside the set fall into a cycle, so rather
+// than iterating them all the way to the limit, each new `z` is compared to a
+// saved one, which is moved up to the current `z` after 1, 2, 4, 8...
+// iterations; that catches a cycle of any length within a few times its
+// period
+struct Cycle {
+    saved: Complex;
+    age: f32;
+    limit: f32;
+    period: f32;
+};
+
+fn cycle_start(z: Complex) -> Cycle {
+    return Cycle(z, 0.0, 1.0, 0.0);
+}
+
+fn cycle_count(cycle: Cycle, offset: Complex) -> Cycle {
+    // the bookkeeping for a new `z`, `offset` from the saved point. orbits
+    // that come back within a small fraction of a pixel of it are taken to
+    // be periodic; tying the tolerance to the pixel size keeps slowly
+    // escaping orbits from being mistaken for cycles at depth. otherwise,
+    // once it's time to move the saved point up, `age` goes back to 0, and
+    // the caller saves `z`
+    var tolerance = camera.pixel_size * 0.001;
+    var res = cycle;
+    res.age = res.age + 1.0;
+    if (complex_norm_sqr(offset) < tolerance * tolerance) {
+        res.period = res.age;
+    }
+    else if (res.age >= res.limit) {
+        res.age = 0.0;
+        res.limit = res.limit * 2.0;
+    }
+    return res;
+}
+
+fn cycle_step(cycle: Cycle, z: Complex) -> Cycle {
+    var res = cycle_count(cycle, csub(z, cycle.saved));
+    if (res.age == 0.0) {
+        res.saved = z;
+    }
+    return res;
+}
+
+// The closest an orbit has come to the orbit trap, in units of `trap.size`;
+// or for the image trap, where on the image it first landed (negative until
+// it has). This is only measured in the orbit trap coloring mode
+struct TrapState {
+    distance: f32;
+    hit: vec2<f32>;
+};
+
+fn trap_start() -> TrapState {
+    return TrapState(1.0e30, vec2<f32>(-1.0, -1.0));
+}
+
+fn trap_local(z: Complex) -> Complex {
+    // `z` relative to the trap's center, in the trap's (rotated) frame
+    var d = csub(z, trap.center);
+    return Complex(d.re * trap.rotation_cos + d.im * trap.rotation_sin, d.im * trap.rotation_cos - d.re * trap.rotation_sin);
+}
+
+fn trap_image_hit(z: Complex) -> vec2<f32> {
+    // where `z` lands on the trap image, from 0 to 1 across it, if it's on a
+    // part that isn't transparent
+    var p = trap_local(z);
+    var uv = vec2<f32>(p.re, -p.im) / (2.0 * trap.size) + vec2<f32>(0.5, 0.5);
+    if (uv.x < 0.0 || uv.y < 0.0 || uv.x >= 1.0 || uv.y >= 1.0) {
+        return vec2<f32>(-1.0, -1.0);
+    }
+    var size = vec2<f32>(textureDimensions(trap_image));
+    if (textureLoad(trap_image, vec2<i32>(uv * size), 0).a < 0.5) {
+        return vec2<f32>(-1.0, -1.0);
+    }
+    return uv;
+}
+
+fn trap_distance(z: Complex) -> f32 {
+    var p = trap_local(z);
+    if (trap.shape == 1u) {
+        return abs(p.im);
+    }
+    if (trap.shape == 2u) {
+        return min(abs(p.re), abs(p.im));
+    }
+    if (trap.shape == 3u) {
+        return abs(sqrt(complex_norm_sqr(p)) - trap.size);
+    }
+    return sqrt(complex_norm_sqr(p));
+}
+
+fn trap_step(state: TrapState, z: Complex) -> TrapState {
+    var res = state;
+    if (coloring.mode != 2u) {
+        return res;
+    }
+    if (trap.shape == 4u) {
+        if (res.hit.x < 0.0) {
+            res.hit = trap_image_hit(z);
+        }
+    }
+    else {
+        res.distance = min(res.distance, trap_distance(z) / trap.size);
+    }
+    return res;
+}
+
+// The averaging colorings add up a value from each step of the orbit, and
+// color by the mean. The mean jumps wherever the iteration count does, so
+// it's blended with the mean without the last step, by the same fraction
+// that smooths the iteration count (see `orbit_value`)
+struct Average {
+    sum: f32;
+    last: f32;
+    count: f32;
+};
+
+fn average_term(z: Complex, z_prev: Complex, z_prev2: Complex, c: Complex) -> f32 {
+    // the value, from 0 to 1, for the step from `z_prev` to `z`
+    if (coloring.mode == 3u) {
+        // stripe average: which way `z` points, in `stripe_density` stripes
+        // around the circle
+        return 0.5 * sin(coloring.stripe_density * atan2(z.im, z.re)) + 0.5;
+    }
+    if (coloring.mode == 4u) {
+        // triangle inequality average: where `|z|` falls between the bounds
+        // the triangle inequality puts on it, from `|z_prev^2|` (taken to be
+        // `|z - c|`, which is exact for `z^2 + c`) and `|c|`
+        var power = sqrt(complex_norm_sqr(csub(z, c)));
+        var c_norm = sqrt(complex_norm_sqr(c));
+        var low = abs(power - c_norm);
+        var high = power + c_norm;
+        if (high <= low) {
+            return 0.0;
+        }
+        return (sqrt(complex_norm_sqr(z)) - low) / (high - low);
+    }
+    // curvature average: how sharply the orbit turns at `z_prev`
+    var before = csub(z_prev, z_prev2);
+    if (complex_norm_sqr(before) == 0.0) {
+        return 0.0;
+    }
+    var turn = cdiv(csub(z, z_prev), before);
+    return abs(atan2(turn.im, turn.re)) / 3.14159265;
+}
+
+fn is_averaging() -> bool {
+    // whether the coloring mode is one of the averages
+    return coloring.mode >= 3u && coloring.mode <= 5u;
+}
+
+struct OrbitState {
+    cycle: Cycle;
+    trapped: TrapState;
+    average: Average;
+    z_prev: Complex;
+    z_prev2: Complex;
+    steps: f32;
+};
+
+fn orbit_start(z: Complex) -> OrbitState {
+    return OrbitState(cycle_start(z), trap_start(), Average(0.0, 0.0, 0.0), z, z, 0.0);
+}
+
+fn orbit_measure(orbit: OrbitState, z: Complex, c: Complex) -> OrbitState {
+    // `orbit_step`, without the cycle detection; for loops that detect
+    // cycles their own way, or can't
+    var res = orbit;
+    res.trapped = trap_step(res.trapped, z);
+
+    // the first step's terms are degenerate (e.g. `z_1 = c` from the
+    // Mandelbrot set's critical point), so they're left out
+    if (is_averaging() && res.steps > 0.0) {
+        var term = average_term(z, res.z_prev, res.z_prev2, c);
+        res.average.sum = res.average.sum + term;
+        res.average.last = term;
+        res.average.count = res.average.count + 1.0;
+    }
+    res.z_prev2 = res.z_prev;
+    res.z_prev = z;
+    res.steps = res.steps + 1.0;
+    return res;
+}
+
+fn orbit_step(orbit: OrbitState, z: Complex, c: Complex) -> OrbitState {
+    // the hook for each new `z` of the orbit, iterated with `c`
+    var res = orbit;
+    res.cycle = cycle_step(res.cycle, z);
+    return orbit_measure(res, z, c);
+}
+
+fn escape_value_with_trap(n: f32, norm_sqr: f32, state: TrapState) -> vec4<f32> {
+    // `escape_value`, plus how the orbit met the trap (see `TrapState`)
+    if (trap.shape == 4u) {
+        return vec4<f32>(n, sqrt(norm_sqr), state.hit.x, state.hit.y);
+    }
+    return vec4<f32>(n, sqrt(norm_sqr), state.distance, 0.0);
+}
+
+fn orbit_value(orbit: OrbitState, n: f32, norm_sqr: f32, fraction: f32) -> vec4<f32> {
+    // what a loop outputs for an orbit that escaped: `escape_value`, with
+    // `n` as for that, plus whatever the coloring mode measured. `fraction`
+    // is the `smooth_fraction` that was taken off the iteration count
+    if (coloring.mode == 2u) {
+        return escape_value_with_trap(n, norm_sqr, orbit.trapped);
+    }
+    if (is_averaging() && orbit.average.count > 0.0) {
+        var mean = orbit.average.sum / orbit.average.count;
+        var previous = mean;
+        if (orbit.average.count > 1.0) {
+            previous = (orbit.average.sum - orbit.average.last) / (orbit.average.count - 1.0);
+        }
+        return vec4<f32>(n, sqrt(norm_sqr), mix(mean, previous, clamp(fraction, 0.0, 1.0)), 0.0);
+    }
+    return escape_value(n, norm_sqr);
+}
@@ -0,0 +1,27 @@
+#include "common_df64.wgsl"
+#include "orbit.wgsl"
+
+// `orbit.wgsl`'s hooks, for the emulated double precision shaders. Traps and
+// averages only need `z` roughly, so are measured in f32. Cycles are compared
+// in df64, though: at the zooms these shaders are used for, points a
+// fraction of a pixel apart can round to the same f32, which would pass for
+// a cycle. So the saved point is kept here, rather than in `Cycle`
+
+struct OrbitDf64 {
+    orbit: OrbitState;
+    saved: Df64Complex;
+};
+
+fn orbit_df64_start(z: Df64Complex) -> OrbitDf64 {
+    return OrbitDf64(orbit_start(df64_complex_to_f32(z)), z);
+}
+
+fn orbit_df64_step(state: OrbitDf64, z: Df64Complex, c: Df64Complex) -> OrbitDf64 {
+    var res = state;
+    res.orbit.cycle = cycle_count(res.orbit.cycle, df64_complex_to_f32(df64_csub(z, res.saved)));
+    if (res.orbit.cycle.age == 0.0) {
+        res.saved = z;
+    }
+    res.orbit = orbit_measure(res.orbit, df64_complex_to_f32(z), df64_complex_to_f32(c));
+    return res;
+}
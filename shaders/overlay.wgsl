@@ -0,0 +1,43 @@
+// Draws the rubber-band selection rectangle on top of the fractal. This is
+// drawn over the same full-screen quad, and discards everything except the
+// rectangle's outline and (faintly tinted) interior
+
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+struct Selection {
+    // opposite corners of the rectangle, in pixel coordinates
+    min: vec2<f32>;
+    max: vec2<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> selection: Selection;
+
+[[stage(vertex)]]
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let border = 1.5;
+    let pos = in.position.xy;
+    let inside_outer = all(pos >= selection.min - border) && all(pos <= selection.max + border);
+    let inside_inner = all(pos >= selection.min + border) && all(pos <= selection.max - border);
+
+    if (!inside_outer) {
+        discard;
+    }
+    if (inside_inner) {
+        return vec4<f32>(1.0, 1.0, 1.0, 0.15);
+    }
+    return vec4<f32>(1.0, 1.0, 1.0, 0.9);
+}
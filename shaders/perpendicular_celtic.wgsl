@@ -0,0 +1,14 @@
+#include "common.wgsl"
+
+// The Perpendicular Celtic: the Perpendicular Mandelbrot, with the Celtic's
+// `abs` on the real part, i.e. `z -> |x^2 - y^2| - 2i|x|y + c`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    return cadd(Complex(abs(z.re * z.re - z.im * z.im), -2.0 * abs(z.re) * z.im), c);
+}
+
+fn degree() -> f32 {
+    return 2.0;
+}
+
+#include "escape_time.wgsl"
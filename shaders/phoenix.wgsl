@@ -0,0 +1,58 @@
+#include "orbit.wgsl"
+
+// The Phoenix: `z_{n+1} = z_n^2 + c + p * z_{n-1}`, where `p` is given by the
+// `p` and `p-im` parameters. Each step depends on the previous *two*
+// iterates, so unlike the formulas in `escape_time.wgsl`, this carries its
+// own iteration state; and an orbit is only periodic once both iterates
+// come back, so it does its own cycle detection
+
+fn phoenix(z0: Complex, c: Complex) -> vec4<f32> {
+    var p = Complex(parameter(0), parameter(1));
+    var z = z0;
+    var z_prev = Complex(0.0, 0.0);
+    var orbit = orbit_start(z);
+    var saved_prev = z_prev;
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0 || complex_norm_sqr(z) > escape_radius_sqr()) {
+            break;
+        }
+        var z_next = cadd(cadd(cmul(z, z), c), cmul(p, z_prev));
+        z_prev = z;
+        z = z_next;
+        i = i - 1.0;
+
+        // `cycle_step`, with whichever iterate is further from its saved
+        // point deciding
+        var offset = csub(z, orbit.cycle.saved);
+        var offset_prev = csub(z_prev, saved_prev);
+        if (complex_norm_sqr(offset_prev) > complex_norm_sqr(offset)) {
+            offset = offset_prev;
+        }
+        orbit.cycle = cycle_count(orbit.cycle, offset);
+        if (orbit.cycle.age == 0.0) {
+            orbit.cycle.saved = z;
+            saved_prev = z_prev;
+        }
+        orbit = orbit_measure(orbit, z, c);
+        if (orbit.cycle.period > 0.0) {
+            return interior_value(complex_norm_sqr(z), orbit.cycle.period, -1.0);
+        }
+    }
+    if (i < 0.0) {
+        return interior_value(complex_norm_sqr(z), 0.0, -1.0);
+    }
+    var fraction = smooth_fraction(complex_norm_sqr(z), 2.0);
+    return orbit_value(orbit, 1.0 - (max - i - fraction) / max, complex_norm_sqr(z), fraction);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return phoenix(Complex(0.0, 0.0), pixel_to_point(in.position.x, in.position.y));
+}
+
+[[stage(fragment)]]
+fn fs_julia(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return phoenix(pixel_to_point(in.position.x, in.position.y), julia_c());
+}
@@ -0,0 +1,81 @@
+#include "orbit.wgsl"
+
+// The iteration `z^2 + c` shared by `mandelbrot.wgsl` and `julia.wgsl`,
+// which differ only in where `z` and `c` come from, and what the derivative
+// tracked for the distance estimate is taken with respect to
+
+fn interior_distance(z0: Complex, c: Complex, period: f32) -> f32 {
+    // the distance from `c` to the boundary of its hyperbolic component, in
+    // pixels, given a point `z0` near the attracting cycle of `period` that
+    // the orbit fell into. the cycle is refined with Newton's method, then
+    // the first and second derivatives of `z` over one period (with respect
+    // to `z` and `c`) give the estimate
+    var w = z0;
+    for (var step = 0; step < 4; step = step + 1) {
+        var z = w;
+        var dz = Complex(1.0, 0.0);
+        for (var j: f32 = 0.0; j < period; j = j + 1.0) {
+            dz = cmul(cadd(z, z), dz);
+            z = cadd(cmul(z, z), c);
+        }
+        w = csub(w, cdiv(csub(z, w), csub(dz, Complex(1.0, 0.0))));
+    }
+
+    var z = w;
+    var dz = Complex(1.0, 0.0);
+    var dc = Complex(0.0, 0.0);
+    var dzdz = Complex(0.0, 0.0);
+    var dcdz = Complex(0.0, 0.0);
+    for (var j: f32 = 0.0; j < period; j = j + 1.0) {
+        var z2 = cadd(z, z);
+        dcdz = cadd(cmul(z2, dcdz), cmul(cadd(dc, dc), dz));
+        dzdz = cadd(cmul(z2, dzdz), cmul(cadd(dz, dz), dz));
+        dc = cadd(cmul(z2, dc), Complex(1.0, 0.0));
+        dz = cmul(z2, dz);
+        z = cadd(cmul(z, z), c);
+    }
+
+    var numerator = 1.0 - complex_norm_sqr(dz);
+    var denominator = cadd(dcdz, cdiv(cmul(dzdz, dc), csub(Complex(1.0, 0.0), dz)));
+    return numerator / sqrt(complex_norm_sqr(denominator)) / camera.pixel_size;
+}
+
+fn quadratic(z0: Complex, c: Complex, derivative0: Complex, parameter_plane: bool) -> vec4<f32> {
+    // iterate from `z0`, with `derivative0` the derivative of `z0`, in
+    // pixels. on the `parameter_plane`, `c` is the pixel, so each step adds
+    // its derivative; and the interior distance estimate (which is with
+    // respect to `c`) is available
+    var z = z0;
+    var derivative = derivative0;
+    var orbit = orbit_start(z);
+    var max = f32(camera.max_iterations);
+    var i: f32 = max;
+    loop {
+        if (i < 0.0 || complex_norm_sqr(z) > escape_radius_sqr()) {
+            break;
+        }
+        derivative = cmul(cadd(z, z), derivative);
+        if (parameter_plane) {
+            derivative = cadd(derivative, Complex(camera.pixel_size, 0.0));
+        }
+        z = cadd(cmul(z, z), c);
+        i = i - 1.0;
+        orbit = orbit_step(orbit, z, c);
+        if (orbit.cycle.period > 0.0) {
+            var distance = -1.0;
+            if (parameter_plane) {
+                distance = interior_distance(z, c, orbit.cycle.period);
+            }
+            return interior_value(complex_norm_sqr(z), orbit.cycle.period, distance);
+        }
+    }
+    if (i < 0.0) {
+        return interior_value(complex_norm_sqr(z), 0.0, -1.0);
+    }
+    var fraction = smooth_fraction(complex_norm_sqr(z), 2.0);
+    var n = 1.0 - (max - i - fraction) / max;
+    if (coloring.mode == 1u) {
+        return escape_value_with_distance(n, complex_norm_sqr(z), derivative);
+    }
+    return orbit_value(orbit, n, complex_norm_sqr(z), fraction);
+}
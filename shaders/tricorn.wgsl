@@ -0,0 +1,15 @@
+#include "common.wgsl"
+
+// The Tricorn (or Mandelbar): the Mandelbrot iteration on the complex
+// conjugate, i.e. `z -> conj(z)^2 + c`
+
+fn formula(z: Complex, c: Complex) -> Complex {
+    var w = Complex(z.re, -z.im);
+    return cadd(cmul(w, w), c);
+}
+
+fn degree() -> f32 {
+    return 2.0;
+}
+
+#include "escape_time.wgsl"
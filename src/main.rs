@@ -10,7 +10,9 @@ use std::time::{
     SystemTime,
 };
 
+mod options;
 mod renderer;
+use options::Options;
 use renderer::Renderer;
 
 fn main() -> Result<()> {
@@ -19,7 +21,11 @@ fn main() -> Result<()> {
 }
 
 async fn run() -> Result<()> {
-    env_logger::init();
+    // status messages (e.g. which fractal or coloring a key switched to) are
+    // logged at the info level, so they're shown unless `RUST_LOG` says
+    // otherwise
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn,fractal_viewer=info")).init();
+    let options = Options::from_args()?;
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
         .with_title("Interactive Fractal Viewer")
@@ -27,42 +33,101 @@ async fn run() -> Result<()> {
     
     let mut renderer = Renderer::new(
         &window,
-        None,
-        None
+        options.fractal.as_deref(),
     ).await?;
+    if let Some(sensitivity) = options.zoom_sensitivity {
+        renderer.set_zoom_sensitivity(sensitivity);
+    }
+    if let Some(bailout) = options.bailout {
+        renderer.set_bailout(bailout)?;
+    }
+    if let Some(palette) = &options.palette {
+        renderer.load_palette(palette)?;
+    }
+    if let Some(offset) = options.palette_offset {
+        renderer.set_palette_offset(offset);
+    }
+    if let Some(repeat) = options.palette_repeat {
+        renderer.set_palette_repeat(repeat)?;
+    }
+    renderer.set_palette_mirror(options.palette_mirror);
+    if let Some(speed) = options.palette_cycle {
+        renderer.set_palette_cycle(speed)?;
+    }
+    if let Some(mode) = &options.coloring {
+        renderer.set_coloring_mode(mode)?;
+    }
+    if let Some(width) = options.line_width {
+        renderer.set_line_width(width)?;
+    }
+    if let Some(density) = options.stripe_density {
+        renderer.set_stripe_density(density)?;
+    }
+    if let Some(mode) = &options.interior {
+        renderer.set_interior_mode(mode)?;
+    }
+    if let Some(image) = &options.trap_image {
+        renderer.load_trap_image(image)?;
+    }
+    if let Some(shape) = &options.trap {
+        renderer.set_trap_shape(shape)?;
+    }
+    if let Some(center) = options.trap_center {
+        renderer.set_trap_center(center);
+    }
+    if let Some(angle) = options.trap_angle {
+        renderer.set_trap_rotation(angle);
+    }
+    if let Some(size) = options.trap_size {
+        renderer.set_trap_size(size)?;
+    }
+    for (name, value) in &options.parameters {
+        renderer.set_parameter(name, *value)?;
+    }
+    for (name, from, to, duration) in &options.animations {
+        renderer.animate_parameter(name, *from, *to, *duration)?;
+    }
+    if let Some(roots) = &options.roots {
+        renderer.set_polynomial_roots(roots)?;
+    }
+    if let Some(coefficients) = &options.coefficients {
+        renderer.set_polynomial_coefficients(coefficients)?;
+    }
+    if let Some(sequence) = &options.sequence {
+        renderer.set_lyapunov_sequence(sequence)?;
+    }
    
     let mut last_time: SystemTime = SystemTime::now();
-    let mut dt: Duration = last_time.elapsed().unwrap();
 
     event_loop.run(move |event, _, control_flow|
         match event {
-            Event::WindowEvent { ref event, window_id, } if window_id == window.id() => {
-                if !renderer.input(&window, event) {
-                    match event {
-                        WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
-                            input:
-                                KeyboardInput {
-                                    state: ElementState::Pressed,
-                                    virtual_keycode: Some(VirtualKeyCode::Escape),
-                                    ..
-                                },
-                            ..
-                        } => *control_flow = ControlFlow::Exit,
-                        WindowEvent::Resized(physical_size) => {
-                            renderer.resize(*physical_size);
-                        }
-                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                            // new_inner_size is &&mut so we have to dereference it twice
-                            renderer.resize(**new_inner_size);
-                        }
-                        _ => {}
+            Event::WindowEvent { ref event, window_id, } if window_id == window.id() && !renderer.input(&window, event) => {
+                match event {
+                    WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                ..
+                            },
+                        ..
+                    } => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(physical_size) => {
+                        renderer.resize(*physical_size);
                     }
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        // new_inner_size is &&mut so we have to dereference it twice
+                        renderer.resize(**new_inner_size);
+                    }
+                    _ => {}
                 }
             },
             Event::RedrawRequested(window_id) if window_id == window.id() => {
-                dt = last_time.elapsed().unwrap();
+                let dt: Duration = last_time.elapsed().unwrap();
                 last_time = SystemTime::now();
-                renderer.update(&dt);
+                if let Err(e) = renderer.update(&dt) {
+                    eprintln!("{:?}", e);
+                }
                 match renderer.render() {
                     Ok(_) => {}
                     // The system is out of memory, we should probably quit
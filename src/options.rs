@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+use crate::renderer::Complex;
+
+/// Command-line options, e.g. `fractal-viewer --zoom-sensitivity 0.5`, or
+/// `fractal-viewer --fractal multibrot --param power=3 --bailout 1000`, or
+/// `fractal-viewer --fractal multibrot --animate power=2:5:10`, or
+/// `fractal-viewer --fractal newton --roots "1;-1;0,1;0,-1"`, or
+/// `fractal-viewer --fractal lyapunov --sequence AABAB`, or
+/// `fractal-viewer --palette sunset.ggr --palette-repeat 4 --palette-mirror`, or
+/// `fractal-viewer --coloring distance --line-width 2 --interior period`, or
+/// `fractal-viewer --coloring orbit-trap --trap cross --trap-center 0,0.5`, or
+/// `fractal-viewer --coloring stripe --stripe-density 8`
+#[derive(Debug, Default)]
+pub struct Options {
+    /// How far each scroll-wheel notch zooms, as a power of two
+    pub zoom_sensitivity: Option<f64>,
+
+    /// Name of the fractal to start with
+    pub fractal: Option<String>,
+
+    /// Escape radius for escape-time fractals
+    pub bailout: Option<f32>,
+
+    /// Gradient file to color escape-time fractals with
+    pub palette: Option<PathBuf>,
+
+    /// How far along the palette to start
+    pub palette_offset: Option<f32>,
+
+    /// How many times to repeat the palette
+    pub palette_repeat: Option<f32>,
+
+    /// Whether to run every other repeat of the palette backwards
+    pub palette_mirror: bool,
+
+    /// Palette lengths per second to cycle the palette by
+    pub palette_cycle: Option<f32>,
+
+    /// Name of the coloring mode, e.g. `distance`
+    pub coloring: Option<String>,
+
+    /// Width of the lines in distance coloring, in pixels
+    pub line_width: Option<f32>,
+
+    /// Number of stripes in stripe average coloring
+    pub stripe_density: Option<f32>,
+
+    /// Name of the interior coloring mode, e.g. `period`
+    pub interior: Option<String>,
+
+    /// Name of the orbit trap's shape, e.g. `circle`
+    pub trap: Option<String>,
+
+    /// Where the orbit trap is, on the complex plane
+    pub trap_center: Option<Complex>,
+
+    /// Angle of the orbit trap, in degrees
+    pub trap_angle: Option<f64>,
+
+    /// Size of the orbit trap
+    pub trap_size: Option<f32>,
+
+    /// Image file to use as the orbit trap
+    pub trap_image: Option<PathBuf>,
+
+    /// Starting values for the fractal's parameters, as `(name, value)`
+    pub parameters: Vec<(String, f32)>,
+
+    /// Parameters to sweep back and forth, as `(name, from, to, seconds)`
+    pub animations: Vec<(String, f32, f32, f64)>,
+
+    /// Roots of the polynomial for Newton's method fractals
+    pub roots: Option<Vec<Complex>>,
+
+    /// Coefficients of the polynomial for Newton's method fractals, lowest
+    /// order first
+    pub coefficients: Option<Vec<Complex>>,
+
+    /// `A`/`B` sequence for Lyapunov fractals
+    pub sequence: Option<String>,
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--zoom-sensitivity" => {
+                    options.zoom_sensitivity = Some(Self::value(&arg, args.next())?);
+                },
+                "--fractal" => {
+                    options.fractal = Some(Self::value(&arg, args.next())?);
+                },
+                "--bailout" => {
+                    options.bailout = Some(Self::value(&arg, args.next())?);
+                },
+                "--palette" => {
+                    options.palette = Some(Self::value(&arg, args.next())?);
+                },
+                "--palette-offset" => {
+                    options.palette_offset = Some(Self::value(&arg, args.next())?);
+                },
+                "--palette-repeat" => {
+                    options.palette_repeat = Some(Self::value(&arg, args.next())?);
+                },
+                "--palette-mirror" => {
+                    options.palette_mirror = true;
+                },
+                "--palette-cycle" => {
+                    options.palette_cycle = Some(Self::value(&arg, args.next())?);
+                },
+                "--coloring" => {
+                    options.coloring = Some(Self::value(&arg, args.next())?);
+                },
+                "--line-width" => {
+                    options.line_width = Some(Self::value(&arg, args.next())?);
+                },
+                "--stripe-density" => {
+                    options.stripe_density = Some(Self::value(&arg, args.next())?);
+                },
+                "--interior" => {
+                    options.interior = Some(Self::value(&arg, args.next())?);
+                },
+                "--trap" => {
+                    options.trap = Some(Self::value(&arg, args.next())?);
+                },
+                "--trap-center" => {
+                    options.trap_center = Some(Self::complex(&arg, args.next())?);
+                },
+                "--trap-angle" => {
+                    options.trap_angle = Some(Self::value(&arg, args.next())?);
+                },
+                "--trap-size" => {
+                    options.trap_size = Some(Self::value(&arg, args.next())?);
+                },
+                "--trap-image" => {
+                    options.trap_image = Some(Self::value(&arg, args.next())?);
+                },
+                "--param" => {
+                    let param: String = Self::value(&arg, args.next())?;
+                    let (name, value) = param.split_once('=')
+                        .ok_or_else(|| anyhow!("{} needs a name=value pair, not {}", arg, param))?;
+                    options.parameters.push((name.to_string(), Self::value(&arg, Some(value.to_string()))?));
+                },
+                "--animate" => {
+                    let animation: String = Self::value(&arg, args.next())?;
+                    let invalid = || anyhow!("{} needs a name=from:to:seconds value, not {}", arg, animation);
+                    let (name, range) = animation.split_once('=').ok_or_else(invalid)?;
+                    let range: Vec<&str> = range.split(':').collect();
+                    if range.len() != 3 {
+                        return Err(invalid());
+                    }
+                    options.animations.push((
+                        name.to_string(),
+                        Self::value(&arg, Some(range[0].to_string()))?,
+                        Self::value(&arg, Some(range[1].to_string()))?,
+                        Self::value(&arg, Some(range[2].to_string()))?,
+                    ));
+                },
+                "--roots" => {
+                    options.roots = Some(Self::complex_list(&arg, args.next())?);
+                },
+                "--coefficients" => {
+                    options.coefficients = Some(Self::complex_list(&arg, args.next())?);
+                },
+                "--sequence" => {
+                    options.sequence = Some(Self::value(&arg, args.next())?);
+                },
+                _ => return Err(anyhow!("unknown argument {}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn value<T>(arg: &str, value: Option<String>) -> Result<T> where T: std::str::FromStr, T::Err: std::error::Error + Send + Sync + 'static {
+        let value = value.ok_or_else(|| anyhow!("{} needs a value", arg))?;
+        value.parse().with_context(|| format!("invalid value for {}: {}", arg, value))
+    }
+
+    fn complex(arg: &str, value: Option<String>) -> Result<Complex> {
+        //! Parse a single complex number, as for [`Options::complex_list`]
+
+        match Self::complex_list(arg, value)?.as_slice() {
+            [number] => Ok(*number),
+            _ => Err(anyhow!("{} needs a single complex number", arg)),
+        }
+    }
+
+    fn complex_list(arg: &str, value: Option<String>) -> Result<Vec<Complex>> {
+        //! Parse a list of complex numbers, separated by `;`. Each is either
+        //! `re,im`, or just `re` for a real number
+
+        let value: String = Self::value(arg, value)?;
+        value.split(';')
+            .map(|number| {
+                let (re, im) = number.split_once(',').unwrap_or((number, "0"));
+                Ok(Complex::new(
+                    Self::value(arg, Some(re.trim().to_string()))?,
+                    Self::value(arg, Some(im.trim().to_string()))?,
+                ))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,80 @@
+use lerp::Lerp;
+use super::bigcomplex::BigComplex;
+use super::complex::Complex;
+
+/// A transition of the camera from one origin/zoom to another, played out
+/// over [`Self::DURATION`]
+///
+/// Zoom is interpolated in log-space, so each doubling takes the same amount
+/// of time, and the apparent speed is constant. The origin follows the zoom,
+/// such that the point the transition "zooms around" stays fixed on screen
+#[derive(Debug, Clone)]
+pub struct CameraAnimation {
+    start_origin: BigComplex,
+    start_zoom: f64,
+
+    target_origin: BigComplex,
+    target_zoom: f64,
+
+    /// `target_origin - start_origin`; small enough for an `f64`
+    offset: Complex,
+
+    /// Seconds since the animation started
+    elapsed: f64,
+}
+
+impl CameraAnimation {
+    /// Length of each transition, in seconds
+    pub const DURATION: f64 = 0.25;
+
+    pub fn new(start_origin: &BigComplex, start_zoom: f64, target_origin: &BigComplex, target_zoom: f64) -> Self {
+        Self {
+            start_origin: start_origin.clone(),
+            start_zoom,
+            target_origin: target_origin.clone(),
+            target_zoom,
+            offset: (target_origin.clone() - start_origin.clone()).to_complex(),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, dt: f64) {
+        self.elapsed += dt;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= Self::DURATION
+    }
+
+    pub fn get_target(&self) -> (&BigComplex, f64) {
+        (&self.target_origin, self.target_zoom)
+    }
+
+    pub fn current(&self) -> (BigComplex, f64) {
+        //! The origin and zoom at this point in the animation
+
+        if self.is_finished() {
+            return (self.target_origin.clone(), self.target_zoom);
+        }
+
+        let t = self.elapsed / Self::DURATION;
+        let zoom = self.start_zoom.ln().lerp(self.target_zoom.ln(), t).exp();
+
+        // for a zoom from `z0` to `z1`, the fixed point `P` satisfies:
+        //   origin(t) = P + (start - P) * z0 / zoom(t)
+        // which, relative to the start, is `offset * progress`:
+        let ratio = self.start_zoom / self.target_zoom;
+        let progress = if (1.0 - ratio).abs() < 1e-9 {
+            // (nearly) no zoom, so this is a plain pan
+            0.0.lerp(1.0, t)
+        }
+        else {
+            (1.0 - self.start_zoom / zoom) / (1.0 - ratio)
+        };
+
+        let offset = Complex::new(self.offset.re * progress, self.offset.im * progress);
+        let origin = self.start_origin.clone() + BigComplex::from_complex(&offset, self.start_origin.bits());
+
+        (origin, zoom)
+    }
+}
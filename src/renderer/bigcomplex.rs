@@ -0,0 +1,304 @@
+use std::ops::{Add, Sub, Mul};
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use super::complex::Complex;
+
+/// An arbitrary-precision fixed-point number. The value is
+/// `mantissa * 2^-bits`, so @bits controls the precision
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigFloat {
+    mantissa: BigInt,
+    bits: u32,
+}
+
+/// A [`Complex`] built from [`BigFloat`]s. This is used where `f64` isn't
+/// enough; i.e. the camera origin and reference orbit at deep zoom levels
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigComplex {
+    pub re: BigFloat,
+    pub im: BigFloat,
+}
+
+impl BigFloat {
+    pub fn zero(bits: u32) -> Self {
+        Self {
+            mantissa: BigInt::zero(),
+            bits,
+        }
+    }
+
+    pub fn from_f64(value: f64, bits: u32) -> Self {
+        //! Convert @value exactly (assuming @bits is large enough to hold
+        //! all of its fractional bits)
+
+        if value == 0.0 || !value.is_finite() {
+            return Self::zero(bits);
+        }
+
+        // decompose into `value = mantissa * 2^exponent`
+        let raw = value.to_bits();
+        let raw_exponent = ((raw >> 52) & 0x7ff) as i64;
+        let raw_fraction = (raw & ((1 << 52) - 1)) as i64;
+        let (mantissa, exponent) = match raw_exponent {
+            0 => (raw_fraction, -1074),
+            _ => (raw_fraction | (1 << 52), raw_exponent - 1075),
+        };
+        let mantissa = BigInt::from(if value < 0.0 { -mantissa } else { mantissa });
+
+        let shift = exponent + bits as i64;
+        Self {
+            mantissa: if shift >= 0 { mantissa << shift } else { mantissa >> -shift },
+            bits,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // an f64 can only hold 53 bits anyway, so drop the excess first. this
+        // keeps the mantissa within range of `BigInt::to_f64`
+        let excess = self.mantissa.bits() as i64 - 64;
+        let (mantissa, bits) = if excess > 0 {
+            (&self.mantissa >> excess, self.bits as i64 - excess)
+        }
+        else {
+            (self.mantissa.clone(), self.bits as i64)
+        };
+
+        // split the scale in two, so values near the bottom of the f64 range
+        // don't underflow before the multiplication
+        let half = (bits / 2) as i32;
+        let rest = (bits - bits / 2) as i32;
+        mantissa.to_f64().unwrap_or(0.0) * 2f64.powi(-half) * 2f64.powi(-rest)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn with_bits(&self, bits: u32) -> Self {
+        //! Re-scale to @bits of precision. If @bits is lower than the current
+        //! precision, the low bits are truncated
+
+        Self {
+            mantissa: Self::rescale(&self.mantissa, self.bits, bits),
+            bits,
+        }
+    }
+
+    fn rescale(mantissa: &BigInt, from: u32, to: u32) -> BigInt {
+        if to >= from {
+            mantissa << (to - from)
+        }
+        else {
+            mantissa >> (from - to)
+        }
+    }
+}
+
+impl Add for BigFloat {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let bits = self.bits.max(other.bits);
+        Self {
+            mantissa: Self::rescale(&self.mantissa, self.bits, bits) + Self::rescale(&other.mantissa, other.bits, bits),
+            bits,
+        }
+    }
+}
+
+impl Sub for BigFloat {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let bits = self.bits.max(other.bits);
+        Self {
+            mantissa: Self::rescale(&self.mantissa, self.bits, bits) - Self::rescale(&other.mantissa, other.bits, bits),
+            bits,
+        }
+    }
+}
+
+impl Mul for BigFloat {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        // the product has `self.bits + other.bits` fractional bits; keep the
+        // larger of the two precisions
+        Self {
+            mantissa: (self.mantissa * other.mantissa) >> self.bits.min(other.bits),
+            bits: self.bits.max(other.bits),
+        }
+    }
+}
+
+impl BigComplex {
+    pub fn zero(bits: u32) -> Self {
+        Self {
+            re: BigFloat::zero(bits),
+            im: BigFloat::zero(bits),
+        }
+    }
+
+    pub fn from_complex(value: &Complex, bits: u32) -> Self {
+        Self {
+            re: BigFloat::from_f64(value.re, bits),
+            im: BigFloat::from_f64(value.im, bits),
+        }
+    }
+
+    pub fn to_complex(&self) -> Complex {
+        Complex::new(self.re.to_f64(), self.im.to_f64())
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.re.bits().max(self.im.bits())
+    }
+
+    pub fn with_bits(&self, bits: u32) -> Self {
+        Self {
+            re: self.re.with_bits(bits),
+            im: self.im.with_bits(bits),
+        }
+    }
+
+    pub fn sqr(&self) -> Self {
+        //! Square this number. Cheaper than `self * self`, as it only needs
+        //! three multiplications
+
+        let re = self.re.clone() * self.re.clone() - self.im.clone() * self.im.clone();
+        let im = self.re.clone() * self.im.clone();
+        Self {
+            re,
+            im: im.clone() + im,
+        }
+    }
+}
+
+impl Add for BigComplex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl Sub for BigComplex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+impl Mul for BigComplex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re.clone() * other.re.clone() - self.im.clone() * other.im.clone(),
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shallow enough that every value below fits in an `f64` exactly
+    const BITS: u32 = 64;
+
+    fn assert_close(actual: f64, expected: f64) {
+        let tolerance = 1e-15 * expected.abs().max(1.0);
+        assert!((actual - expected).abs() <= tolerance, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn f64_round_trip() {
+        for value in [0.0, 1.0, -1.0, 0.1, -0.75, 3.0 * 2f64.powi(-40), -2.0, 1234.5678] {
+            assert_eq!(BigFloat::from_f64(value, BITS).to_f64(), value);
+        }
+    }
+
+    #[test]
+    fn f64_round_trip_truncates_below_precision() {
+        // 2^-10 can't be held with only 8 fractional bits
+        assert_eq!(BigFloat::from_f64(1.0 + 2f64.powi(-10), 8).to_f64(), 1.0);
+        assert_eq!(BigFloat::from_f64(0.5, 8).to_f64(), 0.5);
+    }
+
+    #[test]
+    fn non_finite_is_zero() {
+        assert_eq!(BigFloat::from_f64(f64::NAN, BITS).to_f64(), 0.0);
+        assert_eq!(BigFloat::from_f64(f64::INFINITY, BITS).to_f64(), 0.0);
+    }
+
+    #[test]
+    fn arithmetic_matches_f64() {
+        let values = [0.3, -0.7, 1.25, -2.5e-3, 0.0];
+        for a in values {
+            for b in values {
+                let big_a = BigFloat::from_f64(a, BITS);
+                let big_b = BigFloat::from_f64(b, BITS);
+                assert_close((big_a.clone() + big_b.clone()).to_f64(), a + b);
+                assert_close((big_a.clone() - big_b.clone()).to_f64(), a - b);
+                assert_close((big_a * big_b).to_f64(), a * b);
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_bits_keep_the_larger_precision() {
+        let a = BigFloat::from_f64(-0.375, 60);
+        let b = BigFloat::from_f64(1.625, 100);
+
+        let sum = a.clone() + b.clone();
+        assert_eq!(sum.bits(), 100);
+        assert_close(sum.to_f64(), 1.25);
+
+        let difference = a.clone() - b.clone();
+        assert_eq!(difference.bits(), 100);
+        assert_close(difference.to_f64(), -2.0);
+
+        let product = a * b;
+        assert_eq!(product.bits(), 100);
+        assert_close(product.to_f64(), -0.609375);
+    }
+
+    #[test]
+    fn with_bits_rescales() {
+        let value = BigFloat::from_f64(-3.0625, 64);
+        assert_eq!(value.with_bits(128).to_f64(), -3.0625);
+        assert_eq!(value.with_bits(128).with_bits(64), value);
+        assert_eq!(value.with_bits(4).to_f64(), -3.0625);
+    }
+
+    #[test]
+    fn complex_matches_f64() {
+        let a = Complex::new(0.25, -0.5);
+        let b = Complex::new(-1.5, 0.125);
+        let big_a = BigComplex::from_complex(&a, BITS);
+        let big_b = BigComplex::from_complex(&b, BITS);
+
+        let expected = a * a;
+        let actual = big_a.sqr().to_complex();
+        assert_close(actual.re, expected.re);
+        assert_close(actual.im, expected.im);
+
+        let expected = a * b;
+        let actual = (big_a.clone() * big_b.clone()).to_complex();
+        assert_close(actual.re, expected.re);
+        assert_close(actual.im, expected.im);
+
+        let expected = a - b;
+        let actual = (big_a - big_b).to_complex();
+        assert_close(actual.re, expected.re);
+        assert_close(actual.im, expected.im);
+    }
+}
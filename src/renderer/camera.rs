@@ -5,31 +5,45 @@ use wgpu::util::DeviceExt;
 use winit;
 use winit::event;
 use winit::event::WindowEvent;
-use cgmath::Vector2;
+use cgmath::{InnerSpace, Vector2};
+use std::collections::HashMap;
 use std::time::Duration;
-use lerp::Lerp;
-use super::complex::Complex;
-use super::renderer::Renderer;
+use super::complex::{Complex, SplitComplex};
+use super::bigcomplex::BigComplex;
+use super::animation::CameraAnimation;
 
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-#[repr(C)]
+#[derive(Debug, Clone)]
 pub struct CameraState {
     /// Width of the viewport, in pixels
-    width: f32,
+    width: f64,
 
     /// Height of the viewport, in pixels
-    height: f32,
+    height: f64,
+
+    /// Top-left corner of the viewport within the window, in pixels. This is
+    /// zero, unless the camera only covers part of the window (see
+    /// [`Camera::set_viewport`])
+    viewport_x: f64,
+    viewport_y: f64,
 
     /// The viewport center (x = w/2, y = h/2) corresponds to this
-    /// origin point, on the complex plane
-    origin: Complex,
+    /// origin point, on the complex plane. This is kept in arbitrary
+    /// precision, as deep zooms need more bits than an `f64` can hold
+    origin: BigComplex,
 
     /// The initial scale; used to ensure the fractal fits completely
     /// in the default viewport after opening
-    scale: f32,
+    scale: f64,
 
     /// The zoom level;
-    zoom: f32,
+    zoom: f64,
+
+    /// Rotation of the view around the viewport center, in radians
+    rotation: f64,
+
+    /// The constant `c` for Julia sets. Unused when exploring the Mandelbrot
+    /// set, where `c` varies per pixel instead
+    julia_c: Complex,
 
     /// The complex number corresponding to 0,0 in viewport coordinates
     /// These numbers are used to perform the "zooming"; the closer min
@@ -41,7 +55,47 @@ pub struct CameraState {
     max: Complex,
 
     /// Has the Camera changed at all, and requires a from-scratch redraw?
-    needs_redraw: u32,
+    needs_redraw: bool,
+}
+
+/// The GPU-side view of [`CameraState`]. The origin is uploaded as a split
+/// hi/lo pair, so the shader can work with the (small) per-pixel offset from
+/// the origin, rather than the (imprecise) absolute `f32` coordinates
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct CameraUniform {
+    /// See [`CameraState::origin`]
+    origin: SplitComplex,
+
+    /// See [`CameraState::julia_c`]
+    julia_c: SplitComplex,
+
+    /// Width of the viewport, in pixels
+    width: f32,
+
+    /// Height of the viewport, in pixels
+    height: f32,
+
+    /// The distance between two adjacent pixels, on the complex plane
+    pixel_size: f32,
+
+    /// See [`CameraState::zoom`]
+    zoom: f32,
+
+    /// See [`CameraState::rotation`]; uploaded pre-calculated, since every
+    /// pixel needs them
+    rotation_cos: f32,
+    rotation_sin: f32,
+
+    /// See [`CameraState::viewport_x`]; the shader sees window coordinates,
+    /// so has to subtract this
+    viewport_x: f32,
+    viewport_y: f32,
+
+    /// See [`CameraState::max_iterations`]
+    max_iterations: u32,
+
+    _padding: [u32; 3],
 }
 
 #[derive(Debug)]
@@ -58,27 +112,60 @@ pub struct Camera {
     cursor_pos: Vector2<f64>,
     mouse_left_down: bool,
     grab_pos: Vector2<f64>,
-    grab_point: Complex,
+    grab_delta: Complex,
+
+    /// Keyboard modifiers, for shift + drag selection
+    modifiers: event::ModifiersState,
+
+    /// Where the current rubber-band selection started, in pixels; see
+    /// [`Self::get_selection`]
+    selection_start: Option<Vector2<f64>>,
+
+    /// The transition currently being played out, if any. Zooming is
+    /// animated; see [`Self::animate`]
+    animation: Option<CameraAnimation>,
+
+    /// How far each scroll "notch" zooms, as a power of two; see
+    /// [`Self::set_zoom_sensitivity`]
+    zoom_sensitivity: f64,
+
+    /// Active touch points (by touch ID), for pinch/rotate gestures
+    touches: HashMap<u64, Vector2<f64>>,
+
+    /// Where right-clicking resets the origin to
+    default_origin: Complex,
 }
 
 impl CameraState {
-    pub fn new(width: f32, height: f32, scale: f32, origin: Complex) -> Self {
+    /// Past this point, the per-pixel offsets become too small for the
+    /// shaders' `f32`s
+    pub const MAX_ZOOM: f64 = 1e33;
+
+    /// Upper limit on the number of iterations, however deep the zoom
+    pub const MAX_ITERATIONS: u32 = 1 << 17;
+
+    pub fn new(width: f64, height: f64, scale: f64, origin: Complex) -> Self {
         let zoom = 1.0;
         let (min, max) = Self::calculate_limits(width, height, scale, &origin, zoom);
+        let origin = BigComplex::from_complex(&origin, Self::origin_bits(zoom));
 
         Self {
             width,
             height,
+            viewport_x: 0.0,
+            viewport_y: 0.0,
             origin,
             scale,
             zoom,
+            rotation: 0.0,
+            julia_c: Complex::new(0.0, 0.0),
             min,
             max,
-            needs_redraw: 1,
+            needs_redraw: true,
         }
     }
     
-    fn resize(&mut self, width: f32, height: f32) {
+    fn resize(&mut self, width: f64, height: f64) {
         self.width = width;
         self.height = height;
         self.update_limits();
@@ -89,53 +176,98 @@ impl CameraState {
         //! Manually set the origin position. This corresponds with the center
         //! of the viewport (screen)
 
-        self.origin = origin;
+        self.origin = BigComplex::from_complex(&origin, Self::origin_bits(self.zoom));
+        self.update_limits();
+        self.redraw();
+    }
+
+    fn set_view(&mut self, origin: BigComplex, zoom: f64) {
+        //! Set the origin and zoom level together, keeping the origin's
+        //! precision in line with the zoom
+
+        self.zoom = zoom.min(Self::MAX_ZOOM);
+        self.origin = origin.with_bits(Self::origin_bits(self.zoom));
         self.update_limits();
         self.redraw();
     }
 
-    fn set_zoom(&mut self, zoom: f32) {
+    fn translate(&mut self, offset: Complex) {
+        //! Move the origin by @offset. Unlike [Self::set_origin], this is
+        //! precise at any zoom level, since only the (small) offset has to
+        //! pass through an `f64`
+
+        self.origin = self.origin.clone() + BigComplex::from_complex(&offset, self.origin.bits());
+        self.update_limits();
+        self.redraw();
+    }
+
+    fn set_zoom(&mut self, zoom: f64) {
         //! Manually set the zoom level. Note that this *only* overrides
         //! the zoom float, it does not perform any centering logic. So,
         //! this effectively zooms around the center of the screen
         //!
         //! See [Self::zoom_at] to zoom around a specific pixel
 
-        self.zoom = zoom;
+        self.zoom = zoom.min(Self::MAX_ZOOM);
+        self.origin = self.origin.with_bits(Self::origin_bits(self.zoom));
         self.update_limits();
         self.redraw();
     }
 
-    fn zoom_at_point(&mut self, x: f32, y: f32, zoom_by: f32) {
-        //! Zoom in by @zoom_by, around the pixel coordinates (@x, @y)
+    fn set_rotation(&mut self, rotation: f64) {
+        //! Manually set the rotation, in radians. Like [Self::set_zoom],
+        //! this rotates around the center of the screen
+
+        self.rotation = rotation % std::f64::consts::TAU;
+        self.redraw();
+    }
+
+    fn zoom_at_point(&mut self, x: f64, y: f64, zoom_by: f64) {
+        //! Zoom in by 2^@zoom_by, around the pixel coordinates (@x, @y).
+        //! @zoom_by can be fractional, and negative values zoom out
         //! 
         //! This differs from [Self::set_zoom] in that you can specify
         //! a zoom origin, and the function will attempt to keep that
         //! point on the screen stationary
         
-        let mut offset_pre = self.pixel_to_point(x, y) - self.origin;
-        if zoom_by > 0.0 {
-            self.zoom *= 2.0;
-        }
-        else if zoom_by < 0.0 {
-            self.zoom /= 2.0;
-        }
-        self.update_limits();
-        let mut offset_post = self.pixel_to_point(x, y) - self.origin;
-        self.set_origin(self.origin + (offset_pre - offset_post));
-        self.redraw();
+        let offset_pre = self.pixel_to_delta(x, y);
+        self.set_zoom(self.zoom * zoom_by.exp2());
+        let offset_post = self.pixel_to_delta(x, y);
+        self.translate(offset_pre - offset_post);
+    }
+
+    fn pinch(&mut self, from: Vector2<f64>, to: Vector2<f64>, zoom_by: f64, rotate_by: f64) {
+        //! Apply a two-finger gesture: the point under pixel @from moves to
+        //! pixel @to, while the view is scaled by @zoom_by (a plain factor,
+        //! not a power of two) and rotated by @rotate_by radians around it
 
-        //TODO: zoom such that (x, y)'s associated complex nums do not change
-        // for now, this is just zooming around `self.origin`
-        //let point = self.pixel_to_point(x, y);
+        let offset_pre = self.pixel_to_delta(from.x, from.y);
+        self.set_zoom(self.zoom * zoom_by);
+        self.set_rotation(self.rotation - rotate_by);
+        let offset_post = self.pixel_to_delta(to.x, to.y);
+        self.translate(offset_pre - offset_post);
     }
 
-    fn zoom_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
-        unimplemented!();
+    fn zoom_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        //! Zoom so that the pixel rectangle (@x, @y, @w, @h) fills the
+        //! viewport. The viewport's aspect ratio is kept, so the rectangle is
+        //! fit inside the viewport, centered along its shorter side
+        //!
+        //! @w and @h may be negative (i.e. a rectangle dragged up or left).
+        //! Rectangles smaller than a pixel are ignored
+
+        if w.abs() < 1.0 || h.abs() < 1.0 {
+            return;
+        }
+
+        let center = self.pixel_to_delta(x + w / 2.0, y + h / 2.0);
+        let zoom_by = (self.width / w.abs()).min(self.height / h.abs());
+        self.translate(center);
+        self.set_zoom(self.zoom * zoom_by);
     }
 
     fn redraw(&mut self) {
-        self.needs_redraw = 1;
+        self.needs_redraw = true;
     }
 
     fn update_limits(&mut self) {
@@ -144,10 +276,17 @@ impl CameraState {
         //! 
         //! Note that internally, this function uses [Self::calculate_limits]
 
-        (self.min, self.max) = Self::calculate_limits(self.width, self.height, self.scale, &self.origin, self.zoom);
+        (self.min, self.max) = Self::calculate_limits(self.width, self.height, self.scale, &self.origin.to_complex(), self.zoom);
+    }
+
+    fn origin_bits(zoom: f64) -> u32 {
+        //! The precision needed for the origin at @zoom; enough to address
+        //! individual pixels, with plenty to spare
+
+        64 + zoom.max(1.0).log2().ceil() as u32
     }
 
-    fn calculate_limits(width: f32, height: f32, scale: f32, origin: &Complex, zoom: f32) -> (Complex, Complex) {
+    fn calculate_limits(width: f64, height: f64, scale: f64, origin: &Complex, zoom: f64) -> (Complex, Complex) {
         //! Calculate new `min` and `max` points based on @width/@height,
         //! @scale and @zoom, and @origin.
 
@@ -170,23 +309,73 @@ impl CameraState {
         (min, max)
     }
 
-    fn pixel_to_point(&self, x: f32, y: f32) -> Complex {
-        let w = self.max.re - self.min.re;
-        let h = self.min.im - self.max.im;
+    fn max_iterations(&self) -> u32 {
+        //! The iteration limit at the current zoom. Deeper zooms need more
+        //! iterations to resolve the detail near the boundary, so the limit
+        //! scales with the number of halvings
+        //!
+        //! This only steps up once per doubling of the zoom, so the reference
+        //! orbit isn't recalculated on every frame of an animated zoom
+
+        let iterations = 255.0 + 64.0 * self.zoom.max(1.0).log2().floor();
+        (iterations as u32).min(Self::MAX_ITERATIONS)
+    }
+
+    fn pixel_size(&self) -> f64 {
+        //! The distance between two adjacent pixels, on the complex plane.
+        //! This is the same along both axes (see [Self::calculate_limits])
+
+        self.scale / self.width.max(self.height) / self.zoom
+    }
+
+    fn pixel_to_delta(&self, x: f64, y: f64) -> Complex {
+        //! The offset of pixel (@x, @y) from the origin, on the complex plane
+
+        let pixel_size = self.pixel_size();
+        let dx = (x - self.width / 2.0) * pixel_size;
+        let dy = (y - self.height / 2.0) * pixel_size;
+        let (sin, cos) = self.rotation.sin_cos();
         Complex {
-            re: self.min.re + x * w / self.width,
-            im: self.min.im - y * h / self.height,
+            re: dx * cos - dy * sin,
+            im: dx * sin + dy * cos,
+        }
+    }
+
+    fn pixel_to_point(&self, x: f64, y: f64) -> Complex {
+        //! The point on the complex plane at pixel (@x, @y). This is only as
+        //! precise as an `f64`; see [Self::pixel_to_delta] for deep zooms
+
+        self.origin.to_complex() + self.pixel_to_delta(x, y)
+    }
+
+    fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            origin: self.origin.to_complex().split(),
+            julia_c: self.julia_c.split(),
+            width: self.width as f32,
+            height: self.height as f32,
+            pixel_size: self.pixel_size() as f32,
+            zoom: self.zoom as f32,
+            rotation_cos: self.rotation.cos() as f32,
+            rotation_sin: self.rotation.sin() as f32,
+            viewport_x: self.viewport_x as f32,
+            viewport_y: self.viewport_y as f32,
+            max_iterations: self.max_iterations(),
+            _padding: [0; 3],
         }
     }
 }
 
 impl Camera {
-    pub fn new(device: &wgpu::Device, width: f32, height: f32, scale: f32, origin: Complex) -> Result<Self> {
+    /// Scroll distance of one "notch", for high-resolution (pixel) scrolling
+    pub const PIXELS_PER_NOTCH: f64 = 50.0;
+
+    pub fn new(device: &wgpu::Device, width: f64, height: f64, scale: f64, origin: Complex) -> Result<Self> {
         let state = CameraState::new(width, height, scale, origin);
         let buffer = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
                 label: Some("camera_buffer"),
-                contents: bytemuck::cast_slice(&[state]),
+                contents: bytemuck::cast_slice(&[state.uniform()]),
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             }
         );
@@ -194,7 +383,7 @@ impl Camera {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -218,7 +407,7 @@ impl Camera {
 
         let cursor_pos = Vector2::new(0.0, 0.0);
         let grab_pos = Vector2::new(0.0, 0.0);
-        let grab_point = Complex::new(0.0, 0.0);
+        let grab_delta = Complex::new(0.0, 0.0);
 
         Ok(Self {
             state,
@@ -228,21 +417,42 @@ impl Camera {
             cursor_pos,
             mouse_left_down: false,
             grab_pos,
-            grab_point,
+            grab_delta,
+            modifiers: event::ModifiersState::empty(),
+            selection_start: None,
+            animation: None,
+            zoom_sensitivity: 1.0,
+            touches: HashMap::new(),
+            default_origin: origin,
         })
     }
 
-    pub fn input(&mut self, window: &winit::window::Window, event: &winit::event::WindowEvent) -> bool {
+    pub fn input(&mut self, _window: &winit::window::Window, event: &winit::event::WindowEvent) -> bool {
         match event {
             WindowEvent::MouseWheel { delta, .. } => {
-                match delta {
-                    event::MouseScrollDelta::LineDelta(_horizontal, vertical) => {
-                        self.zoom_at_point(self.cursor_pos.x as f32, self.cursor_pos.y as f32, *vertical);
-                        true
-                    },
-                    _ => false
-                }
-                
+                let notches = match delta {
+                    event::MouseScrollDelta::LineDelta(_horizontal, vertical) => *vertical as f64,
+                    event::MouseScrollDelta::PixelDelta(position) => position.y / Self::PIXELS_PER_NOTCH,
+                };
+                self.zoom_at_point(self.cursor_pos.x, self.cursor_pos.y, notches * self.zoom_sensitivity);
+                true
+            },
+            WindowEvent::Touch(touch) => {
+                self.touch(touch);
+                true
+            },
+            WindowEvent::TouchpadMagnify { delta, .. } => {
+                // the view is scaled by 1 + @delta, like the rest of macOS
+                let cursor = self.cursor_pos;
+                self.pinch(cursor, cursor, (1.0 + delta).max(0.01), 0.0);
+                true
+            },
+            WindowEvent::TouchpadRotate { delta, .. } => {
+                // @delta is in degrees, counterclockwise, where the pinch
+                // angle is clockwise on screen
+                let cursor = self.cursor_pos;
+                self.pinch(cursor, cursor, 1.0, -(*delta as f64).to_radians());
+                true
             },
             WindowEvent::CursorMoved { position, .. } => {
                 self.cursor_pos.x = position.x;
@@ -252,80 +462,250 @@ impl Camera {
             WindowEvent::MouseInput { state, button, .. } => {
                 match (button, state) {
                     (event::MouseButton::Left, event::ElementState::Released) => {
+                        if let Some(start) = self.selection_start.take() {
+                            let size = self.cursor_pos - start;
+                            self.zoom_rect(start.x, start.y, size.x, size.y);
+                        }
                         self.mouse_left_down = false;
                         // window.set_cursor_icon(winit::window::CursorIcon::Default)
                         true
                     },
-                    (event::MouseButton::Left, event::ElementState::Pressed) => {
-                        if !self.mouse_left_down {
-                            self.mouse_left_down = true;
-                            self.grab_pos = self.cursor_pos.clone();
-                            self.grab_point = self.pixel_to_point(self.grab_pos.x as f32, self.grab_pos.y as f32);
-
-                        }
+                    (event::MouseButton::Left, event::ElementState::Pressed) if self.modifiers.shift() && !self.mouse_left_down => {
+                        self.selection_start = Some(self.cursor_pos);
+                        true
+                    },
+                    (event::MouseButton::Left, event::ElementState::Pressed) if !self.mouse_left_down && self.selection_start.is_none() => {
+                        self.mouse_left_down = true;
+                        self.animation = None;
+                        self.grab_pos = self.cursor_pos;
+                        self.grab_delta = self.state.pixel_to_delta(self.grab_pos.x, self.grab_pos.y);
                         // window.set_cursor_icon(winit::window::CursorIcon::Hand)
                         true
                     },
+                    (event::MouseButton::Left, event::ElementState::Pressed) => true,
                     (event::MouseButton::Right, event::ElementState::Pressed) => {
-                        self.set_origin(Renderer::DEFAULT_CAMERA_ORIGIN);
-                        self.set_zoom(1.0);
+                        let default_origin = self.default_origin;
+                        self.state.set_rotation(0.0);
+                        self.animate(|target| {
+                            target.set_origin(default_origin);
+                            target.set_zoom(1.0);
+                        });
                         true
                     }
                     _ => false
                 }
             }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+                false
+            },
             _ => false,
         }
     }
 
-    pub fn update(&mut self, dt: &Duration, queue: &wgpu::Queue) {
+    pub fn update(&mut self, dt: &Duration, queue: &wgpu::Queue) -> bool {
+        //! Advance any animation or drag by @dt, and upload the view if it
+        //! changed. Returns whether it did, so that anything built up over
+        //! several frames of the old view can be thrown away
+
+        if let Some(animation) = &mut self.animation {
+            animation.advance(dt.as_secs_f64());
+            let (origin, zoom) = animation.current();
+            self.state.set_view(origin, zoom);
+            if animation.is_finished() {
+                self.animation = None;
+            }
+        }
         if self.mouse_left_down {
-            let grab_complex = self.pixel_to_point(self.cursor_pos.x as f32, self.cursor_pos.y as f32);
-            self.state.set_origin(self.grab_point + self.state.origin - grab_complex);
+            // keep the grabbed point under the cursor; if it's already there,
+            // nothing has changed
+            let cursor_delta = self.state.pixel_to_delta(self.cursor_pos.x, self.cursor_pos.y);
+            let offset = self.grab_delta - cursor_delta;
+            if offset.re != 0.0 || offset.im != 0.0 {
+                self.state.translate(offset);
+            }
+            self.grab_delta = cursor_delta;
         }
-        if self.state.needs_redraw != 0 {
-            let a = self.pixel_to_point(100.0, 100.0);
-            let b = self.pixel_to_point(101.0, 100.0);
-            println!("{:?} x {:?}", a, b);
-            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.state]));
-            self.state.needs_redraw = 0;
+        if self.state.needs_redraw {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.state.uniform()]));
+            self.state.needs_redraw = false;
+            return true;
         }
+
+        false
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
-        self.state.resize(width as f32, height as f32);
+        self.state.resize(width as f64, height as f64);
     }
 
-    pub fn set_origin(&mut self, origin: Complex){
-        //! Manually set the origin position. This corresponds with the center
-        //! of the viewport (screen)
-
+    pub fn reset(&mut self, scale: f64, origin: Complex) {
+        //! Jump straight to a new default view, centered on @origin and
+        //! @scale wide (e.g. after switching fractals). Right-clicking
+        //! returns here from then on
+
+        self.animation = None;
+        self.mouse_left_down = false;
+        self.selection_start = None;
+        self.default_origin = origin;
+        self.state.scale = scale;
+        self.state.set_rotation(0.0);
+        self.state.set_zoom(1.0);
         self.state.set_origin(origin);
     }
 
-    pub fn set_zoom(&mut self, zoom: f32) {
-        //! Manually set the zoom level. Note that this *only* overrides
-        //! the zoom float, it does not perform any centering logic. So,
-        //! this effectively zooms around the center of the screen
+    pub fn set_viewport(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        //! Only cover the rectangle (@x, @y, @width, @height) of the window,
+        //! e.g. for a picture-in-picture inset. Pixel coordinates passed to
+        //! the camera are still relative to the viewport, not the window
+
+        self.state.viewport_x = x;
+        self.state.viewport_y = y;
+        self.state.resize(width, height);
+    }
+
+    pub fn zoom_at_point(&mut self, x: f64, y: f64, zoom_by: f64) {
+        //! Zoom in by 2^@zoom_by, around the pixel coordinates (@x, @y),
+        //! keeping that point on the screen stationary
         //!
-        //! See [Self::zoom_at] to zoom around a specific pixel
+        //! The zoom is animated, and builds on any zoom already in progress,
+        //! so scrolling quickly zooms in quickly
 
-        self.state.set_zoom(zoom);
+        self.animate(|target| target.zoom_at_point(x, y, zoom_by));
     }
 
-    pub fn zoom_at_point(&mut self, x: f32, y: f32, zoom_by: f32) {
-        //! Zoom in by @zoom_by, around the pixel coordinates (@x, @y)
-        //! 
-        //! This differs from [Self::set_zoom] in that you can specify
-        //! a zoom origin, and the function will attempt to keep that
-        //! point on the screen stationary
+    pub fn zoom_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        //! Zoom so that the pixel rectangle (@x, @y, @w, @h) fills the
+        //! viewport, keeping the viewport's aspect ratio
+        //!
+        //! The rectangle is relative to what's on screen right now, so any
+        //! animation in progress is abandoned (from wherever it got to)
 
-        self.state.zoom_at_point(x, y, zoom_by);
+        self.animation = None;
+        self.animate(|target| target.zoom_rect(x, y, w, h));
     }
 
-    pub fn zoom_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+    pub fn pinch(&mut self, from: Vector2<f64>, to: Vector2<f64>, zoom_by: f64, rotate_by: f64) {
+        //! Apply a pinch/rotate gesture; see [CameraState::pinch]. Gestures
+        //! track the user's fingers directly, so they aren't animated
 
-        self.state.zoom_rect(x, y, w, h);
+        self.animation = None;
+        self.state.pinch(from, to, zoom_by, rotate_by);
+    }
+
+    pub fn set_julia_c(&mut self, c: Complex) {
+        //! Set the constant `c`, for rendering Julia sets. Setting the same
+        //! `c` again doesn't count as a change
+
+        if self.state.julia_c != c {
+            self.state.julia_c = c;
+            self.state.redraw();
+        }
+    }
+
+    pub fn get_cursor_point(&self) -> Complex {
+        //! The point on the complex plane under the mouse cursor
+
+        self.state.pixel_to_point(self.cursor_pos.x, self.cursor_pos.y)
+    }
+
+    pub fn set_zoom_sensitivity(&mut self, sensitivity: f64) {
+        //! Set how far each scroll-wheel notch zooms in, as a power of two;
+        //! i.e. at `1.0` (the default) each notch doubles the zoom level.
+        //! High-resolution scrolling (touchpads) is scaled to match
+
+        self.zoom_sensitivity = sensitivity;
+    }
+
+    fn touch(&mut self, touch: &event::Touch) {
+        //! Track touch points, and turn two-finger movement into a pinch
+        //! (zoom), rotate and pan gesture
+
+        let position = Vector2::new(touch.location.x, touch.location.y);
+        match touch.phase {
+            event::TouchPhase::Started => {
+                self.touches.insert(touch.id, position);
+            },
+            event::TouchPhase::Ended | event::TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+            },
+            event::TouchPhase::Moved => {
+                let previous = match self.touches.insert(touch.id, position) {
+                    Some(previous) => previous,
+                    None => return,
+                };
+                if self.touches.len() != 2 {
+                    return;
+                }
+                let other = *self.touches.iter()
+                    .find(|(id, _)| **id != touch.id)
+                    .map(|(_, other)| other)
+                    .unwrap();
+
+                let span_pre = previous - other;
+                let span_post = position - other;
+                if span_pre.magnitude() < 1.0 || span_post.magnitude() < 1.0 {
+                    return;
+                }
+                self.pinch(
+                    (previous + other) / 2.0,
+                    (position + other) / 2.0,
+                    span_post.magnitude() / span_pre.magnitude(),
+                    span_post.y.atan2(span_post.x) - span_pre.y.atan2(span_pre.x),
+                );
+            },
+        }
+    }
+
+    fn animate<F>(&mut self, change: F) where F: FnOnce(&mut CameraState) {
+        //! Animate from the current view to the view produced by @change.
+        //! @change is applied on top of the target of any animation already
+        //! in progress, and the new animation replaces it
+
+        let mut target = self.state.clone();
+        if let Some(animation) = &self.animation {
+            let (origin, zoom) = animation.get_target();
+            target.set_view(origin.clone(), zoom);
+        }
+        change(&mut target);
+
+        self.animation = Some(CameraAnimation::new(
+            &self.state.origin,
+            self.state.zoom,
+            &target.origin,
+            target.zoom,
+        ));
+    }
+
+    pub fn get_selection(&self) -> Option<(Vector2<f64>, Vector2<f64>)> {
+        //! The rubber-band selection currently being dragged out (shift +
+        //! left drag), as two opposite corners in pixel coordinates
+
+        self.selection_start.map(|start| (start, self.cursor_pos))
+    }
+
+    pub fn get_width(&self) -> f64 {
+        self.state.width
+    }
+
+    pub fn get_height(&self) -> f64 {
+        self.state.height
+    }
+
+    pub fn get_zoom(&self) -> f64 {
+        self.state.zoom
+    }
+
+    pub fn get_origin(&self) -> &BigComplex {
+        &self.state.origin
+    }
+
+    pub fn get_max_iterations(&self) -> u32 {
+        self.state.max_iterations()
+    }
+
+    pub fn get_pixel_size(&self) -> f64 {
+        self.state.pixel_size()
     }
 
     pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
@@ -336,12 +716,83 @@ impl Camera {
         &self.group
     }
 
-    fn pixel_to_point(&self, x: f32, y: f32) -> Complex {
-        let w = self.state.max.re - self.state.min.re;
-        let h = self.state.min.im - self.state.max.im;
-        Complex {
-            re: self.state.min.re + x * w / self.state.width,
-            im: self.state.min.im - y * h / self.state.height,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> CameraState {
+        CameraState::new(800.0, 600.0, 4.0, Complex::new(-0.5, 0.25))
+    }
+
+    fn point(camera: &CameraState, x: f64, y: f64) -> Complex {
+        camera.origin.to_complex() + camera.pixel_to_delta(x, y)
+    }
+
+    fn assert_zoomed(before: CameraState, x: f64, y: f64, w: f64, h: f64, zoom_by: f64) {
+        //! Zoom the 800x600 view @before to the rectangle (@x, @y, @w, @h),
+        //! and check that it was scaled up by @zoom_by around the
+        //! rectangle's center
+
+        let mut after = before.clone();
+        after.zoom_rect(x, y, w, h);
+        assert!((after.zoom / before.zoom - zoom_by).abs() < 1e-12);
+
+        // every pixel in the rectangle ends up the same way around the
+        // viewport's center, just further out
+        let (center_x, center_y) = (x + w / 2.0, y + h / 2.0);
+        let tolerance = 1e-9 * after.pixel_size();
+        for (px, py) in [(x, y), (x + w, y), (x, y + h), (x + w, y + h), (center_x, center_y)] {
+            let actual = point(&after, 400.0 + (px - center_x) * zoom_by, 300.0 + (py - center_y) * zoom_by);
+            let expected = point(&before, px, py);
+            assert!(
+                (actual.re - expected.re).abs() <= tolerance && (actual.im - expected.im).abs() <= tolerance,
+                "{:?} != {:?}", actual, expected,
+            );
+        }
+    }
+
+    #[test]
+    fn zoom_rect_wide() {
+        // wider than the viewport, so the width fills it
+        assert_zoomed(camera(), 100.0, 100.0, 200.0, 50.0, 4.0);
+    }
+
+    #[test]
+    fn zoom_rect_tall() {
+        // taller than the viewport, so the height fills it
+        assert_zoomed(camera(), 500.0, 200.0, 100.0, 200.0, 3.0);
+    }
+
+    #[test]
+    fn zoom_rect_dragged_backwards() {
+        assert_zoomed(camera(), 300.0, 150.0, -200.0, -50.0, 4.0);
+        assert_zoomed(camera(), 300.0, 150.0, 200.0, -50.0, 4.0);
+    }
+
+    #[test]
+    fn zoom_rect_rotated() {
+        for (rotation, (x, y, w, h), zoom_by) in [
+            (std::f64::consts::FRAC_PI_2, (100.0, 100.0, 200.0, 50.0), 4.0),
+            (1.0, (600.0, 50.0, -160.0, 300.0), 2.0),
+        ] {
+            let mut before = camera();
+            before.set_rotation(rotation);
+            assert_zoomed(before, x, y, w, h, zoom_by);
+        }
+    }
+
+    #[test]
+    fn zoom_rect_ignores_tiny_rects() {
+        let mut camera = camera();
+        camera.needs_redraw = false;
+        for (w, h) in [(0.0, 0.0), (0.5, 100.0), (100.0, -0.5), (-0.9, -0.9)] {
+            camera.zoom_rect(200.0, 200.0, w, h);
+            let origin = camera.origin.to_complex();
+            assert_eq!(camera.zoom, 1.0);
+            assert_eq!((origin.re, origin.im), (-0.5, 0.25));
+            assert!(!camera.needs_redraw);
         }
     }
 }
@@ -0,0 +1,529 @@
+use anyhow::{anyhow, Result};
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+use super::Palette;
+use super::OrbitTrap;
+
+/// The GPU-side view of [`Coloring`]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ColoringUniform {
+    /// Escape radius; an orbit has escaped once `|z|` is larger than this
+    bailout: f32,
+
+    /// How far along the palette to start, and how many times to repeat it
+    /// across the whole iteration range
+    palette_offset: f32,
+    palette_repeat: f32,
+
+    /// Whether every other repeat of the palette runs backwards, so that
+    /// there are no seams between repeats
+    palette_mirror: u32,
+
+    /// See [`ColoringMode`]
+    mode: u32,
+
+    /// Width of the fade from the boundary in [`ColoringMode::Distance`],
+    /// in pixels
+    line_width: f32,
+
+    /// See [`InteriorMode`]
+    interior: u32,
+
+    /// Number of stripes around the circle in
+    /// [`ColoringMode::StripeAverage`]
+    stripe_density: f32,
+}
+
+/// How the coloring pass turns each pixel's escape-time results into a color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColoringMode {
+    /// The smooth iteration count, looked up in the palette
+    EscapeTime,
+
+    /// The estimated distance to the set's boundary, relative to the size of
+    /// a pixel; thin black filaments on white. Only shaders that track the
+    /// derivative of `z` estimate distances; the rest fall back to
+    /// [`ColoringMode::EscapeTime`]
+    Distance,
+
+    /// The closest the orbit came to an [`OrbitTrap`], looked up in the
+    /// palette; or with an image trap, the color of the image where the
+    /// orbit first landed on it. Only the Mandelbrot set, its Julia sets, the
+    /// `escape_time.wgsl` fractals, the Phoenix, the Nova and the Magnets
+    /// measure this (the Mandelbrot set and its Julia sets at every
+    /// precision, although with perturbation, the iterations skipped by the
+    /// series approximation aren't measured); the rest fall back to
+    /// [`ColoringMode::EscapeTime`]
+    OrbitTrap,
+
+    /// The average of `sin(density * arg(z))` over the orbit, looked up in
+    /// the palette; stripes that follow the set's shape. This and the other
+    /// averages are measured by the same fractals as the orbit trap, and are
+    /// blended between iteration counts like the smooth iteration count, so
+    /// there are no bands
+    StripeAverage,
+
+    /// The average of where each `|z|` falls between the bounds that the
+    /// triangle inequality puts on it, given the `z` before
+    TriangleInequality,
+
+    /// The average of how sharply the orbit turns at each step
+    Curvature,
+
+    /// The fraction of escaped pixels in view that escaped no later than
+    /// this one, looked up in the palette; histogram equalization, which
+    /// spreads the palette evenly over the view however deep the zoom and
+    /// however high the iteration limit (see [`super::Iterations`])
+    Histogram,
+}
+
+impl ColoringMode {
+    pub const ALL: [ColoringMode; 7] = [
+        ColoringMode::EscapeTime,
+        ColoringMode::Distance,
+        ColoringMode::OrbitTrap,
+        ColoringMode::StripeAverage,
+        ColoringMode::TriangleInequality,
+        ColoringMode::Curvature,
+        ColoringMode::Histogram,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColoringMode::EscapeTime => "escape-time",
+            ColoringMode::Distance => "distance",
+            ColoringMode::OrbitTrap => "orbit-trap",
+            ColoringMode::StripeAverage => "stripe",
+            ColoringMode::TriangleInequality => "triangle-inequality",
+            ColoringMode::Curvature => "curvature",
+            ColoringMode::Histogram => "histogram",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL.iter()
+            .find(|mode| mode.name() == name)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown coloring mode {}", name))
+    }
+
+    pub fn next(&self) -> Self {
+        //! The mode after this one, wrapping around
+
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Settings for how escape-time fractals are colored, shared by every
+/// fractal and camera. This is bound at group 2
+///
+/// Pixels are colored by their normalized iteration count, which smooths
+/// out the bands between iteration counts by looking at how far past the
+/// bailout the orbit ended up (see `smooth_iterations` in `common.wgsl`).
+/// Larger bailouts make that estimate more accurate
+///
+/// The iteration count is then looked up in a [`Palette`], which is uploaded
+/// as a 1D texture. Other [`ColoringMode`]s color by other results
+///
+/// Some settings (the bailout, the mode, the stripe density and the
+/// [`OrbitTrap`]) change what the iteration pass measures, so changing them
+/// means iterating again; the rest only affect the coloring pass
+#[derive(Debug)]
+pub struct Coloring {
+    /// Data uploaded to the GPU
+    uniform: ColoringUniform,
+    needs_upload: bool,
+    needs_iteration: bool,
+    palette: Palette,
+    palette_needs_upload: bool,
+
+    /// Palette lengths per second that the offset moves by while cycling,
+    /// and whether it's cycling at all
+    cycle_speed: f32,
+    cycling: bool,
+
+    /// The orbit trap, for [`ColoringMode::OrbitTrap`]
+    trap: OrbitTrap,
+
+    /// WGPU objects
+    buffer: wgpu::Buffer,
+    palette_texture: wgpu::Texture,
+    _palette_view: wgpu::TextureView,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+/// How the coloring pass colors pixels that never escaped. Orbits inside the
+/// set fall into a cycle, which the Mandelbrot set, its Julia sets, the
+/// `escape_time.wgsl` fractals and the Phoenix detect (so they can stop
+/// iterating early). The Mandelbrot set's perturbation shader can't, so past
+/// `Precision::PERTURBATION_ZOOM` only [`InteriorMode::Magnitude`] works;
+/// nor do the Nova and the Magnets look, as settling down is what colors
+/// their exterior. Pixels without the information a mode needs are black
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteriorMode {
+    /// Plain black
+    Black,
+
+    /// The period of the cycle, looked up in the palette
+    Period,
+
+    /// The final `|z|`, looked up in the palette
+    Magnitude,
+
+    /// The estimated distance to the edge of the component the pixel is in,
+    /// drawn like [`ColoringMode::Distance`]. Only the Mandelbrot set
+    /// estimates this, and only at single precision (below
+    /// `Precision::DOUBLE_ZOOM`); deeper, it needs the cycle pinned down more
+    /// precisely than `f32` can
+    Distance,
+}
+
+impl InteriorMode {
+    pub const ALL: [InteriorMode; 4] = [InteriorMode::Black, InteriorMode::Period, InteriorMode::Magnitude, InteriorMode::Distance];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InteriorMode::Black => "black",
+            InteriorMode::Period => "period",
+            InteriorMode::Magnitude => "magnitude",
+            InteriorMode::Distance => "distance",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL.iter()
+            .find(|mode| mode.name() == name)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown interior coloring mode {}", name))
+    }
+
+    pub fn next(&self) -> Self {
+        //! The mode after this one, wrapping around
+
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Coloring {
+    /// Escape radius to start with; big enough for smooth coloring to be
+    /// seamless
+    pub const DEFAULT_BAILOUT: f32 = 256.0;
+
+    /// Smallest usable escape radius; orbits of the Mandelbrot set that get
+    /// further than 2 from the origin are sure to escape, but ones within it
+    /// might not
+    pub const MIN_BAILOUT: f32 = 2.0;
+
+    /// Number of colors in the palette texture
+    pub const PALETTE_SIZE: u32 = 1024;
+
+    /// Palette cycling speed to start with, in palette lengths per second
+    pub const DEFAULT_CYCLE_SPEED: f32 = 0.1;
+
+    /// Line width to start with, in pixels
+    pub const DEFAULT_LINE_WIDTH: f32 = 1.0;
+
+    /// Stripe density to start with
+    pub const DEFAULT_STRIPE_DENSITY: f32 = 5.0;
+
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = ColoringUniform {
+            bailout: Self::DEFAULT_BAILOUT,
+            palette_offset: 0.0,
+            palette_repeat: 1.0,
+            palette_mirror: 0,
+            mode: ColoringMode::EscapeTime as u32,
+            line_width: Self::DEFAULT_LINE_WIDTH,
+            interior: InteriorMode::Black as u32,
+            stripe_density: Self::DEFAULT_STRIPE_DENSITY,
+        };
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("coloring_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let palette_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("palette_texture"),
+            size: wgpu::Extent3d {
+                width: Self::PALETTE_SIZE,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D1,
+            // palettes are given in sRGB, so have the GPU decode them
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        let palette_view = palette_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let trap = OrbitTrap::new(device)?;
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D1,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("coloring_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&palette_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: trap.get_buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(trap.get_image_view()),
+                },
+            ],
+            label: Some("coloring_bind_group"),
+        });
+
+        Ok(Self {
+            uniform,
+            needs_upload: false,
+            needs_iteration: false,
+            palette: Palette::default(),
+            palette_needs_upload: true,
+            cycle_speed: Self::DEFAULT_CYCLE_SPEED,
+            cycling: false,
+            trap,
+            buffer,
+            palette_texture,
+            _palette_view: palette_view,
+            layout,
+            group,
+        })
+    }
+
+    pub fn set_bailout(&mut self, radius: f32) -> Result<()> {
+        //! Treat orbits as escaped once they're further than @radius from the
+        //! origin
+
+        if !radius.is_finite() || radius < Self::MIN_BAILOUT {
+            return Err(anyhow!("the bailout radius must be at least {}, not {}", Self::MIN_BAILOUT, radius));
+        }
+
+        self.uniform.bailout = radius;
+        self.needs_upload = true;
+        self.needs_iteration = true;
+
+        Ok(())
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.palette_needs_upload = true;
+    }
+
+    pub fn set_palette_offset(&mut self, offset: f32) {
+        //! Start the palette @offset of the way along (it wraps around)
+
+        self.uniform.palette_offset = offset;
+        self.needs_upload = true;
+    }
+
+    pub fn set_palette_repeat(&mut self, repeat: f32) -> Result<()> {
+        //! Run through the palette @repeat times across the iteration range
+
+        if !repeat.is_finite() || repeat <= 0.0 {
+            return Err(anyhow!("the palette repeat count must be positive, not {}", repeat));
+        }
+
+        self.uniform.palette_repeat = repeat;
+        self.needs_upload = true;
+
+        Ok(())
+    }
+
+    pub fn set_palette_mirror(&mut self, mirror: bool) {
+        //! Run every other repeat of the palette backwards
+
+        self.uniform.palette_mirror = mirror as u32;
+        self.needs_upload = true;
+    }
+
+    pub fn set_mode(&mut self, mode: ColoringMode) {
+        self.uniform.mode = mode as u32;
+        self.needs_upload = true;
+        self.needs_iteration = true;
+    }
+
+    pub fn get_mode(&self) -> ColoringMode {
+        ColoringMode::ALL[self.uniform.mode as usize]
+    }
+
+    pub fn set_interior_mode(&mut self, mode: InteriorMode) {
+        self.uniform.interior = mode as u32;
+        self.needs_upload = true;
+    }
+
+    pub fn get_interior_mode(&self) -> InteriorMode {
+        InteriorMode::ALL[self.uniform.interior as usize]
+    }
+
+    pub fn get_trap_mut(&mut self) -> &mut OrbitTrap {
+        &mut self.trap
+    }
+
+    pub fn set_line_width(&mut self, width: f32) -> Result<()> {
+        //! Fade from black to white over @width pixels from the boundary, in
+        //! [`ColoringMode::Distance`]
+
+        if !width.is_finite() || width <= 0.0 {
+            return Err(anyhow!("the line width must be positive, not {}", width));
+        }
+
+        self.uniform.line_width = width;
+        self.needs_upload = true;
+
+        Ok(())
+    }
+
+    pub fn set_stripe_density(&mut self, density: f32) -> Result<()> {
+        //! Wrap @density stripes around the circle in
+        //! [`ColoringMode::StripeAverage`]; higher densities give finer
+        //! stripes
+
+        if !density.is_finite() || density <= 0.0 {
+            return Err(anyhow!("the stripe density must be positive, not {}", density));
+        }
+
+        self.uniform.stripe_density = density;
+        self.needs_upload = true;
+        self.needs_iteration = true;
+
+        Ok(())
+    }
+
+    pub fn set_palette_cycle(&mut self, speed: f32) -> Result<()> {
+        //! Start cycling the palette, moving it along by @speed palette
+        //! lengths per second (negative speeds cycle backwards)
+
+        if !speed.is_finite() {
+            return Err(anyhow!("invalid palette cycling speed {}", speed));
+        }
+
+        self.cycle_speed = speed;
+        self.cycling = true;
+
+        Ok(())
+    }
+
+    pub fn toggle_palette_cycle(&mut self) {
+        //! Pause or resume palette cycling, at the current speed
+
+        self.cycling = !self.cycling;
+    }
+
+    pub fn advance(&mut self, dt: f64) {
+        //! Move the palette along by @dt seconds' worth of cycling
+
+        if self.cycling && self.cycle_speed != 0.0 {
+            // a mirrored palette repeats every 2 lengths, so wrap at that
+            // rather than 1, to avoid a jump
+            let offset = self.uniform.palette_offset as f64 + self.cycle_speed as f64 * dt;
+            self.uniform.palette_offset = offset.rem_euclid(2.0) as f32;
+            self.needs_upload = true;
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) -> bool {
+        //! Upload any changes. Returns whether any of them change what the
+        //! iteration pass measures, in which case the fractal has to be
+        //! iterated again
+
+        if self.needs_upload {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            self.needs_upload = false;
+        }
+        if self.palette_needs_upload {
+            let colors = self.palette.to_rgba8(Self::PALETTE_SIZE as usize);
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.palette_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                bytemuck::cast_slice(&colors),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(4 * Self::PALETTE_SIZE),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width: Self::PALETTE_SIZE,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+            self.palette_needs_upload = false;
+        }
+
+        let trap_changed = self.trap.update(queue);
+        std::mem::replace(&mut self.needs_iteration, false) || trap_changed
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+}
@@ -1,20 +1,68 @@
-use std::ops::{Add, Sub, Mul};
+use std::ops::{Add, Sub, Mul, Div};
 use bytemuck;
 
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Complex {
-    pub re: f32,
-    pub im: f32,
+    pub re: f64,
+    pub im: f64,
+}
+
+/// A [`Complex`] split into high and low `f32` parts, for uploading to the
+/// GPU. The original value is (approximately) `hi + lo`, which keeps ~48 bits
+/// of the mantissa, rather than the 24 bits a single `f32` would keep
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct SplitComplex {
+    pub re_hi: f32,
+    pub re_lo: f32,
+    pub im_hi: f32,
+    pub im_lo: f32,
 }
 
 impl Complex {
-    pub fn new(re: f32, im: f32) -> Self {
+    pub fn new(re: f64, im: f64) -> Self {
         Self {
             re,
             im,
         }
     }
+
+    pub fn norm_sqr(&self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    pub fn conj(&self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn to_f32(self) -> [f32; 2] {
+        [self.re as f32, self.im as f32]
+    }
+
+    pub fn split(&self) -> SplitComplex {
+        let (re_hi, re_lo) = split_f64(self.re);
+        let (im_hi, im_lo) = split_f64(self.im);
+
+        SplitComplex {
+            re_hi,
+            re_lo,
+            im_hi,
+            im_lo,
+        }
+    }
+}
+
+pub fn split_f64(value: f64) -> (f32, f32) {
+    //! Split @value into a (hi, lo) pair of `f32`s, where `hi` is the nearest
+    //! `f32` to @value, and `lo` holds the remainder
+
+    let hi = value as f32;
+    let lo = (value - hi as f64) as f32;
+
+    (hi, lo)
 }
 
 impl Add for Complex {
@@ -48,4 +96,17 @@ impl Mul for Complex {
             im: self.re * other.im + self.im * other.re,
         }
     }
-}
\ No newline at end of file
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let norm_sqr = other.norm_sqr();
+        let numerator = self * other.conj();
+        Self {
+            re: numerator.re / norm_sqr,
+            im: numerator.im / norm_sqr,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+
+/// The GPU-side view of [`DensityMap`]; the counts themselves are in storage
+/// buffers
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct DensityUniform {
+    /// Frames accumulated since the counts were last cleared; also seeds the
+    /// random samples
+    frame: u32,
+
+    /// Random samples taken by each invocation, each frame
+    samples: u32,
+
+    /// Size of the counts buffer, in pixels
+    width: u32,
+    height: u32,
+}
+
+/// Per-pixel orbit counts for density renderers like the Buddhabrot
+///
+/// A compute shader (`density.wgsl`) traces the orbits of random points and
+/// adds up how many pass through each pixel, in three channels. The counts
+/// build up from frame to frame, so the picture gets smoother over time; they
+/// are cleared whenever the view changes. The points are picked by a
+/// Metropolis chain for each invocation, which keeps to points whose orbits
+/// visit the view, so zoomed-in views fill in too
+#[derive(Debug)]
+pub struct DensityMap {
+    /// Data uploaded to the GPU
+    uniform: DensityUniform,
+    needs_clear: bool,
+
+    /// WGPU objects. The counts are written by the compute shader, and read
+    /// by the fragment shader, so each gets its own bind group
+    uniform_buffer: wgpu::Buffer,
+    counts_buffer: wgpu::Buffer,
+    peaks_buffer: wgpu::Buffer,
+    chains_buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+    compute_layout: wgpu::BindGroupLayout,
+    compute_group: wgpu::BindGroup,
+}
+
+impl DensityMap {
+    pub const SHADER: &'static str = "./shaders/density.wgsl";
+
+    /// Counts kept for each pixel (red, green and blue)
+    pub const CHANNELS: u64 = 3;
+
+    /// Workgroups of `cs_accumulate` dispatched each frame, each of 64
+    /// invocations
+    pub const WORKGROUPS: u32 = 256;
+
+    /// Workgroups of `cs_clear` dispatched to clear the counts. Each
+    /// invocation clears every `n`th count, so this doesn't depend on the size
+    pub const CLEAR_WORKGROUPS: u32 = 1024;
+
+    /// Random samples taken by each invocation of `cs_accumulate`, each frame
+    pub const SAMPLES: u32 = 8;
+
+    /// Size of each invocation's chain state: its current point, how long
+    /// that point's orbit takes to escape, and padding
+    const CHAIN_SIZE: u64 = 16;
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Result<Self> {
+        let uniform = DensityUniform {
+            frame: 0,
+            samples: Self::SAMPLES,
+            width,
+            height,
+        };
+        let uniform_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("density_uniform_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let counts_buffer = Self::init_counts_buffer(device, width, height);
+        let peaks_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("density_peaks_buffer"),
+            size: 4 * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let chains_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("density_chains_buffer"),
+            size: Self::WORKGROUPS as u64 * 64 * Self::CHAIN_SIZE,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let layout = Self::init_layout(device, wgpu::ShaderStages::FRAGMENT, true);
+        let compute_layout = Self::init_layout(device, wgpu::ShaderStages::COMPUTE, false);
+        let group = Self::init_bind_group(device, &layout, &uniform_buffer, &counts_buffer, &peaks_buffer, &chains_buffer);
+        let compute_group = Self::init_bind_group(device, &compute_layout, &uniform_buffer, &counts_buffer, &peaks_buffer, &chains_buffer);
+
+        Ok(Self {
+            uniform,
+            needs_clear: true,
+            uniform_buffer,
+            counts_buffer,
+            peaks_buffer,
+            chains_buffer,
+            layout,
+            group,
+            compute_layout,
+            compute_group,
+        })
+    }
+
+    pub fn clear(&mut self) {
+        //! Throw away the counts so far, e.g. because the view has changed
+        //! and they're in the wrong place
+
+        self.needs_clear = true;
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        //! Re-allocate the counts for a @width x @height window, starting
+        //! from scratch
+
+        self.counts_buffer = Self::init_counts_buffer(device, width, height);
+        self.group = Self::init_bind_group(device, &self.layout, &self.uniform_buffer, &self.counts_buffer, &self.peaks_buffer, &self.chains_buffer);
+        self.compute_group = Self::init_bind_group(device, &self.compute_layout, &self.uniform_buffer, &self.counts_buffer, &self.peaks_buffer, &self.chains_buffer);
+        self.uniform.width = width;
+        self.uniform.height = height;
+        self.needs_clear = true;
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) {
+        //! Move on to the next frame of samples, or back to the first if the
+        //! counts need clearing
+
+        if self.needs_clear {
+            self.uniform.frame = 0;
+            self.needs_clear = false;
+        }
+        else {
+            self.uniform.frame += 1;
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+    }
+
+    pub fn is_first_frame(&self) -> bool {
+        //! Whether the counts should be cleared before accumulating this
+        //! frame's samples
+
+        self.uniform.frame == 0
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+
+    pub fn get_compute_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.compute_layout
+    }
+
+    pub fn get_compute_bind_group(&self) -> &wgpu::BindGroup {
+        &self.compute_group
+    }
+
+    fn init_counts_buffer(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("density_counts_buffer"),
+            size: width.max(1) as u64 * height.max(1) as u64 * Self::CHANNELS * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn init_layout(device: &wgpu::Device, visibility: wgpu::ShaderStages, read_only: bool) -> wgpu::BindGroupLayout {
+        //! The counts, peaks and chains are read-only for drawing, but written
+        //! to by the compute shader
+
+        let storage = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility,
+                    ty: storage,
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility,
+                    ty: storage,
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility,
+                    ty: storage,
+                    count: None,
+                },
+            ],
+            label: Some("density_bind_group_layout"),
+        })
+    }
+
+    fn init_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, uniform_buffer: &wgpu::Buffer, counts_buffer: &wgpu::Buffer, peaks_buffer: &wgpu::Buffer, chains_buffer: &wgpu::Buffer) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: counts_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: peaks_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: chains_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("density_bind_group"),
+        })
+    }
+}
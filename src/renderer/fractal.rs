@@ -0,0 +1,524 @@
+use super::Complex;
+use super::renderer::{Mode, Precision};
+
+/// A tweakable input to a fractal's formula, e.g. the power of a Multibrot.
+/// The current values are uploaded to the shader in declaration order (see
+/// [`super::Parameters`])
+#[derive(Debug, Clone, Copy)]
+pub struct Parameter {
+    /// Name used to set the parameter, e.g. from the command line
+    pub name: &'static str,
+
+    /// Value the parameter starts at, and returns to when switching fractals
+    pub default: f32,
+}
+
+/// Extra data that some fractals' shaders need, beyond the camera, the
+/// parameters and the coloring settings. This is bound at group 3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// A high-precision orbit to perturb around (see [`super::ReferenceOrbit`])
+    ReferenceOrbit,
+
+    /// A polynomial's coefficients and roots (see [`super::Polynomial`])
+    Polynomial,
+
+    /// An `A`/`B` sequence (see [`super::Sequence`])
+    Sequence,
+
+    /// Orbit counts, accumulated by a compute shader (see
+    /// [`super::DensityMap`])
+    DensityMap,
+}
+
+/// A fractal formula, and everything the renderer needs to know to show it
+///
+/// Each formula is a set of WGSL files (one per [`Mode`]/[`Precision`] it
+/// supports), plus the view it looks best from. New formulas only need an
+/// implementation of this trait and an entry in [`registry`]
+pub trait Fractal: std::fmt::Debug {
+    /// Human-readable name, also used to pick the fractal by name
+    fn name(&self) -> &'static str;
+
+    /// The shader that renders this fractal in @mode at @precision, or
+    /// `None` if that combination isn't supported
+    fn shader_path(&self, mode: Mode, precision: Precision) -> Option<&'static str>;
+
+    /// The fragment entry point within the shader for @mode
+    fn entry_point(&self, _mode: Mode) -> &'static str {
+        "fs_main"
+    }
+
+    /// The extra data the shader for @precision needs, if any
+    fn resource(&self, _precision: Precision) -> Option<Resource> {
+        None
+    }
+
+    /// The center of the initial view of the parameter plane
+    fn default_origin(&self) -> Complex;
+
+    /// Width of the initial view of the parameter plane
+    fn default_scale(&self) -> f64;
+
+    /// The center of the initial view of the Julia sets
+    fn julia_origin(&self) -> Complex {
+        Complex::new(0.0, 0.0)
+    }
+
+    /// Width of the initial view of the Julia sets
+    fn julia_scale(&self) -> f64 {
+        4.0
+    }
+
+    /// The formula's tweakable inputs; at most [`super::Parameters::MAX`]
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    /// Whether the shader outputs escape-time results (see `escape_value` in
+    /// `common.wgsl`) to be colored with the palette, rather than colors.
+    /// Those results are kept in [`super::Iterations`], so they're only
+    /// rendered again when the view changes
+    fn uses_palette(&self) -> bool {
+        true
+    }
+
+    fn has_julia(&self) -> bool {
+        //! Whether this fractal has Julia sets to explore; i.e. whether there
+        //! is a Julia shader at all
+
+        self.shader_path(Mode::Julia, Precision::Single).is_some()
+    }
+}
+
+/// The classic `z -> z^2 + c`. The only fractal with a perturbation shader,
+/// since [`super::ReferenceOrbit`] iterates exactly this formula
+#[derive(Debug)]
+pub struct Mandelbrot;
+
+impl Mandelbrot {
+    pub const SHADER: &'static str = "./shaders/mandelbrot.wgsl";
+    pub const SHADER_DF64: &'static str = "./shaders/mandelbrot_df64.wgsl";
+    pub const SHADER_PERTURBATION: &'static str = "./shaders/mandelbrot_perturbation.wgsl";
+    pub const JULIA_SHADER: &'static str = "./shaders/julia.wgsl";
+    pub const JULIA_SHADER_DF64: &'static str = "./shaders/julia_df64.wgsl";
+}
+
+impl Fractal for Mandelbrot {
+    fn name(&self) -> &'static str {
+        "mandelbrot"
+    }
+
+    fn shader_path(&self, mode: Mode, precision: Precision) -> Option<&'static str> {
+        match (mode, precision) {
+            (Mode::Mandelbrot, Precision::Single) => Some(Self::SHADER),
+            (Mode::Mandelbrot, Precision::Double) => Some(Self::SHADER_DF64),
+            (Mode::Mandelbrot, Precision::Perturbation) => Some(Self::SHADER_PERTURBATION),
+            (Mode::Julia, Precision::Single) => Some(Self::JULIA_SHADER),
+            (Mode::Julia, Precision::Double) => Some(Self::JULIA_SHADER_DF64),
+            // the reference orbit is for the Mandelbrot set, not the Julia sets
+            (Mode::Julia, Precision::Perturbation) => None,
+        }
+    }
+
+    fn resource(&self, precision: Precision) -> Option<Resource> {
+        match precision {
+            Precision::Perturbation => Some(Resource::ReferenceOrbit),
+            _ => None,
+        }
+    }
+
+    fn default_origin(&self) -> Complex {
+        Complex::new(-0.5, 0.0)
+    }
+
+    fn default_scale(&self) -> f64 {
+        3.0
+    }
+}
+
+/// Newton's method, applied to a polynomial (see [`super::Polynomial`]).
+/// Each pixel is colored by which root it converges to. There's no `c`, so
+/// there are no Julia sets either
+#[derive(Debug)]
+pub struct Newton;
+
+impl Newton {
+    pub const SHADER: &'static str = "./shaders/newton.wgsl";
+    pub const PARAMETERS: [Parameter; 2] = [
+        Parameter { name: "relaxation", default: 1.0 },
+        Parameter { name: "relaxation-im", default: 0.0 },
+    ];
+}
+
+impl Fractal for Newton {
+    fn name(&self) -> &'static str {
+        "newton"
+    }
+
+    fn shader_path(&self, mode: Mode, precision: Precision) -> Option<&'static str> {
+        match (mode, precision) {
+            (Mode::Mandelbrot, Precision::Single) => Some(Self::SHADER),
+            _ => None,
+        }
+    }
+
+    fn resource(&self, _precision: Precision) -> Option<Resource> {
+        Some(Resource::Polynomial)
+    }
+
+    fn default_origin(&self) -> Complex {
+        Complex::new(0.0, 0.0)
+    }
+
+    fn default_scale(&self) -> f64 {
+        4.0
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &Self::PARAMETERS
+    }
+
+    fn uses_palette(&self) -> bool {
+        false
+    }
+}
+
+/// Lyapunov fractals; the stability of the logistic map, as its growth rate
+/// switches between the pixel's two coordinates in a given `A`/`B` sequence
+/// (see [`super::Sequence`]). These have nothing to do with `c`, so there are
+/// no Julia sets
+#[derive(Debug)]
+pub struct Lyapunov;
+
+impl Lyapunov {
+    pub const SHADER: &'static str = "./shaders/lyapunov.wgsl";
+}
+
+impl Fractal for Lyapunov {
+    fn name(&self) -> &'static str {
+        "lyapunov"
+    }
+
+    fn shader_path(&self, mode: Mode, precision: Precision) -> Option<&'static str> {
+        match (mode, precision) {
+            (Mode::Mandelbrot, Precision::Single) => Some(Self::SHADER),
+            _ => None,
+        }
+    }
+
+    fn resource(&self, _precision: Precision) -> Option<Resource> {
+        Some(Resource::Sequence)
+    }
+
+    fn default_origin(&self) -> Complex {
+        // the logistic map is only interesting for growth rates up to 4
+        Complex::new(3.0, 3.0)
+    }
+
+    fn default_scale(&self) -> f64 {
+        2.0
+    }
+
+    fn uses_palette(&self) -> bool {
+        false
+    }
+}
+
+/// The Buddhabrot, and its colored cousin the Nebulabrot; the density of the
+/// escaping orbits of the Mandelbrot set, rather than the set itself (see
+/// [`super::DensityMap`])
+///
+/// The three parameters are the iteration limits of the red, green and blue
+/// channels; an orbit only shows up in channels whose limit it escapes within
+#[derive(Debug)]
+pub struct Buddhabrot {
+    pub name: &'static str,
+    pub parameters: &'static [Parameter],
+}
+
+impl Buddhabrot {
+    pub const SHADER: &'static str = "./shaders/buddhabrot.wgsl";
+
+    /// Every channel has the same limit, so this is in shades of grey
+    pub const BUDDHABROT: Buddhabrot = Buddhabrot {
+        name: "buddhabrot",
+        parameters: &[
+            Parameter { name: "red-iterations", default: 1000.0 },
+            Parameter { name: "green-iterations", default: 1000.0 },
+            Parameter { name: "blue-iterations", default: 1000.0 },
+        ],
+    };
+
+    /// Long orbits in red, and short ones in blue
+    pub const NEBULABROT: Buddhabrot = Buddhabrot {
+        name: "nebulabrot",
+        parameters: &[
+            Parameter { name: "red-iterations", default: 5000.0 },
+            Parameter { name: "green-iterations", default: 500.0 },
+            Parameter { name: "blue-iterations", default: 50.0 },
+        ],
+    };
+}
+
+impl Fractal for Buddhabrot {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn shader_path(&self, mode: Mode, precision: Precision) -> Option<&'static str> {
+        match (mode, precision) {
+            (Mode::Mandelbrot, Precision::Single) => Some(Self::SHADER),
+            _ => None,
+        }
+    }
+
+    fn resource(&self, _precision: Precision) -> Option<Resource> {
+        Some(Resource::DensityMap)
+    }
+
+    fn default_origin(&self) -> Complex {
+        Complex::new(-0.5, 0.0)
+    }
+
+    fn default_scale(&self) -> f64 {
+        3.5
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        self.parameters
+    }
+
+    fn uses_palette(&self) -> bool {
+        false
+    }
+}
+
+/// A fractal defined entirely by a single shader, with an entry point for
+/// each plane: `fs_main` for the parameter plane, and `fs_julia` for the
+/// Julia sets. Most are built on a shared loop, like `escape_time.wgsl`
+///
+/// There are no emulated double precision versions of these, so they're
+/// limited to [`Precision::Single`]
+#[derive(Debug)]
+pub struct Formula {
+    pub name: &'static str,
+    pub shader: &'static str,
+    pub origin: Complex,
+    pub scale: f64,
+    pub julia_origin: Complex,
+    pub julia_scale: f64,
+    pub parameters: &'static [Parameter],
+}
+
+impl Formula {
+    /// `z -> z^d + c`, for an integer, real or complex power `d`. Integer
+    /// powers are calculated exactly, others in polar form (see `cpow` in
+    /// `complex.wgsl`)
+    pub const MULTIBROT: Formula = Formula {
+        name: "multibrot",
+        shader: "./shaders/multibrot.wgsl",
+        origin: Complex { re: 0.0, im: 0.0 },
+        scale: 4.5,
+        julia_origin: Complex { re: 0.0, im: 0.0 },
+        julia_scale: 4.0,
+        parameters: &[
+            Parameter { name: "power", default: 2.0 },
+            Parameter { name: "power-im", default: 0.0 },
+        ],
+    };
+
+    /// The Mandelbrot set's `abs`-based relatives, like the Burning Ship or
+    /// the Tricorn. These only differ in their formula, so each shader is
+    /// built on `escape_time.wgsl`
+    pub const VARIANTS: [Formula; 8] = [
+        Formula {
+            name: "burning-ship",
+            shader: "./shaders/burning_ship.wgsl",
+            origin: Complex { re: -0.5, im: -0.55 },
+            scale: 3.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "tricorn",
+            shader: "./shaders/tricorn.wgsl",
+            origin: Complex { re: -0.55, im: 0.0 },
+            scale: 4.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "celtic",
+            shader: "./shaders/celtic.wgsl",
+            origin: Complex { re: -0.85, im: 0.0 },
+            scale: 4.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "buffalo",
+            shader: "./shaders/buffalo.wgsl",
+            origin: Complex { re: -0.5, im: 0.6 },
+            scale: 3.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "perpendicular-mandelbrot",
+            shader: "./shaders/perpendicular_mandelbrot.wgsl",
+            origin: Complex { re: -0.65, im: 0.0 },
+            scale: 3.25,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "perpendicular-burning-ship",
+            shader: "./shaders/perpendicular_burning_ship.wgsl",
+            origin: Complex { re: -0.5, im: 0.1 },
+            scale: 3.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "perpendicular-celtic",
+            shader: "./shaders/perpendicular_celtic.wgsl",
+            origin: Complex { re: -0.85, im: 0.0 },
+            scale: 3.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "perpendicular-buffalo",
+            shader: "./shaders/perpendicular_buffalo.wgsl",
+            origin: Complex { re: -0.5, im: -0.05 },
+            scale: 3.5,
+            julia_origin: Complex { re: 0.0, im: 0.0 },
+            julia_scale: 4.0,
+            parameters: &[],
+        },
+    ];
+
+    /// `z_{n+1} = z_n^2 + c + p * z_{n-1}`. The classic Phoenix is the Julia
+    /// set for `c = 0.5667`, with the default `p`
+    pub const PHOENIX: Formula = Formula {
+        name: "phoenix",
+        shader: "./shaders/phoenix.wgsl",
+        origin: Complex { re: -0.65, im: 0.0 },
+        scale: 3.0,
+        julia_origin: Complex { re: 0.0, im: 0.0 },
+        julia_scale: 3.0,
+        parameters: &[
+            Parameter { name: "p", default: -0.5 },
+            Parameter { name: "p-im", default: 0.0 },
+        ],
+    };
+
+    /// Relaxed Newton's method for `z^d - 1`, plus `c`
+    pub const NOVA: Formula = Formula {
+        name: "nova",
+        shader: "./shaders/nova.wgsl",
+        origin: Complex { re: -0.6, im: 0.0 },
+        scale: 2.5,
+        julia_origin: Complex { re: 0.0, im: 0.0 },
+        julia_scale: 4.0,
+        parameters: &[
+            Parameter { name: "power", default: 3.0 },
+            Parameter { name: "relaxation", default: 1.0 },
+            Parameter { name: "relaxation-im", default: 0.0 },
+        ],
+    };
+
+    /// The Magnet fractals, from the renormalization of magnetic models;
+    /// see `magnet.wgsl`
+    pub const MAGNETS: [Formula; 2] = [
+        Formula {
+            name: "magnet-1",
+            shader: "./shaders/magnet_1.wgsl",
+            origin: Complex { re: 1.35, im: 0.0 },
+            scale: 5.5,
+            julia_origin: Complex { re: 1.0, im: 0.0 },
+            julia_scale: 6.0,
+            parameters: &[],
+        },
+        Formula {
+            name: "magnet-2",
+            shader: "./shaders/magnet_2.wgsl",
+            origin: Complex { re: 1.0, im: 0.0 },
+            scale: 4.25,
+            julia_origin: Complex { re: 1.0, im: 0.0 },
+            julia_scale: 6.0,
+            parameters: &[],
+        },
+    ];
+}
+
+impl Fractal for Formula {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn shader_path(&self, _mode: Mode, precision: Precision) -> Option<&'static str> {
+        match precision {
+            Precision::Single => Some(self.shader),
+            _ => None,
+        }
+    }
+
+    fn entry_point(&self, mode: Mode) -> &'static str {
+        match mode {
+            Mode::Mandelbrot => "fs_main",
+            Mode::Julia => "fs_julia",
+        }
+    }
+
+    fn default_origin(&self) -> Complex {
+        self.origin
+    }
+
+    fn default_scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn julia_origin(&self) -> Complex {
+        self.julia_origin
+    }
+
+    fn julia_scale(&self) -> f64 {
+        self.julia_scale
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        self.parameters
+    }
+}
+
+pub fn registry() -> Vec<Box<dyn Fractal>> {
+    //! Every fractal the viewer can show, in the order they're cycled through
+
+    let mut fractals: Vec<Box<dyn Fractal>> = vec![
+        Box::new(Mandelbrot),
+        Box::new(Formula::MULTIBROT),
+        Box::new(Newton),
+    ];
+    for variant in Formula::VARIANTS {
+        fractals.push(Box::new(variant));
+    }
+    fractals.push(Box::new(Formula::PHOENIX));
+    fractals.push(Box::new(Formula::NOVA));
+    for magnet in Formula::MAGNETS {
+        fractals.push(Box::new(magnet));
+    }
+    fractals.push(Box::new(Lyapunov));
+    fractals.push(Box::new(Buddhabrot::BUDDHABROT));
+    fractals.push(Box::new(Buddhabrot::NEBULABROT));
+
+    fractals
+}
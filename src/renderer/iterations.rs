@@ -0,0 +1,265 @@
+use anyhow::Result;
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+
+/// A rectangle of pixels for the histogram to leave out, e.g. because the
+/// Julia preview covers it; empty if there isn't one
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct HistogramMask {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Each pixel's escape-time results, kept between frames
+///
+/// Fractals colored with the palette (see [`super::fractal::Fractal::uses_palette`])
+/// are rendered in two passes. The iteration pass runs the fractal's shader,
+/// which writes the pixel's results here rather than a color: the fraction of
+/// the iteration limit left when it escaped (negative for the interior), the
+/// final `|z|`, the estimated distance to the boundary in pixels (negative if
+/// the shader doesn't estimate it), and for the interior, the period of the
+/// cycle the orbit fell into (0 if none was found). That only has to happen when the view changes. The
+/// coloring pass (`coloring.wgsl`) then looks the results up in the palette
+/// every frame, which is cheap; so palette changes and cycling don't need the
+/// fractal to be iterated again
+///
+/// For [`super::ColoringMode::Histogram`], a compute shader (`histogram.wgsl`)
+/// also builds a histogram of the escaped pixels' results after each
+/// iteration pass, and adds it up into the fraction of pixels at or below
+/// each bin, for the coloring pass to look up. Pixels under the
+/// [`HistogramMask`] are left out. Like [`super::DensityMap`],
+/// the histogram is written by the compute shader and read by the fragment
+/// shader, so each gets its own bind group
+#[derive(Debug)]
+pub struct Iterations {
+    /// Size of the results, in pixels
+    width: u32,
+    height: u32,
+
+    /// The pixels left out of the histogram
+    mask: HistogramMask,
+
+    /// WGPU objects
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    histogram_buffer: wgpu::Buffer,
+    cumulative_buffer: wgpu::Buffer,
+    mask_buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+    compute_layout: wgpu::BindGroupLayout,
+    compute_group: wgpu::BindGroup,
+}
+
+impl Iterations {
+    /// Format of the results; the escape value, the final `|z|`, the
+    /// distance estimate and the period
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
+    pub const HISTOGRAM_SHADER: &'static str = "./shaders/histogram.wgsl";
+
+    /// Bins in the histogram; this has to match `HISTOGRAM_BINS` in
+    /// `common.wgsl`
+    pub const HISTOGRAM_BINS: u64 = 1024;
+
+    /// Width and height of the workgroups of the per-pixel histogram passes
+    pub const HISTOGRAM_WORKGROUP_SIZE: u32 = 8;
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Result<Self> {
+        let (texture, view) = Self::init_texture(device, width, height);
+        // the range of results, then the bins
+        let histogram_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("histogram_buffer"),
+            size: (2 + Self::HISTOGRAM_BINS) * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let cumulative_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("histogram_cumulative_buffer"),
+            size: (2 + Self::HISTOGRAM_BINS) * std::mem::size_of::<f32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let mask = HistogramMask::default();
+        let mask_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("histogram_mask_buffer"),
+                contents: bytemuck::cast_slice(&[mask]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let layout = Self::init_layout(device, wgpu::ShaderStages::FRAGMENT, true);
+        let compute_layout = Self::init_layout(device, wgpu::ShaderStages::COMPUTE, false);
+        let group = Self::init_bind_group(device, &layout, &view, &histogram_buffer, &cumulative_buffer, &mask_buffer, true);
+        let compute_group = Self::init_bind_group(device, &compute_layout, &view, &histogram_buffer, &cumulative_buffer, &mask_buffer, false);
+
+        Ok(Self {
+            width,
+            height,
+            mask,
+            _texture: texture,
+            view,
+            histogram_buffer,
+            cumulative_buffer,
+            mask_buffer,
+            layout,
+            group,
+            compute_layout,
+            compute_group,
+        })
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        //! Re-allocate the results for a @width x @height window. They'll
+        //! need rendering again
+
+        let (texture, view) = Self::init_texture(device, width, height);
+        self.group = Self::init_bind_group(device, &self.layout, &view, &self.histogram_buffer, &self.cumulative_buffer, &self.mask_buffer, true);
+        self.compute_group = Self::init_bind_group(device, &self.compute_layout, &view, &self.histogram_buffer, &self.cumulative_buffer, &self.mask_buffer, false);
+        self._texture = texture;
+        self.view = view;
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn set_histogram_mask(&mut self, mask: HistogramMask, queue: &wgpu::Queue) {
+        //! Leave the pixels under @mask out of the histogram, from the next
+        //! time it's built
+
+        if mask != self.mask {
+            self.mask = mask;
+            queue.write_buffer(&self.mask_buffer, 0, bytemuck::cast_slice(&[mask]));
+        }
+    }
+
+    pub fn get_histogram_workgroups(&self) -> (u32, u32) {
+        //! Workgroups to dispatch for the per-pixel histogram passes, to
+        //! cover every pixel
+
+        let size = Self::HISTOGRAM_WORKGROUP_SIZE;
+        (self.width.max(1).div_ceil(size), self.height.max(1).div_ceil(size))
+    }
+
+    pub fn get_view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+
+    pub fn get_compute_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.compute_layout
+    }
+
+    pub fn get_compute_bind_group(&self) -> &wgpu::BindGroup {
+        &self.compute_group
+    }
+
+    fn init_texture(device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("iterations_texture"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    fn init_layout(device: &wgpu::Device, visibility: wgpu::ShaderStages, read_only: bool) -> wgpu::BindGroupLayout {
+        //! The coloring pass only reads the results and the cumulative
+        //! histogram; the compute shader also builds the histogram itself,
+        //! skipping the pixels under the mask
+
+        let storage = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let mut entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility,
+                ty: storage,
+                count: None,
+            },
+        ];
+        if !read_only {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility,
+                ty: storage,
+                count: None,
+            });
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            });
+        }
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &entries,
+            label: Some("iterations_bind_group_layout"),
+        })
+    }
+
+    fn init_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, view: &wgpu::TextureView, histogram_buffer: &wgpu::Buffer, cumulative_buffer: &wgpu::Buffer, mask_buffer: &wgpu::Buffer, read_only: bool) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: cumulative_buffer.as_entire_binding(),
+            },
+        ];
+        if !read_only {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 2,
+                resource: histogram_buffer.as_entire_binding(),
+            });
+            entries.push(wgpu::BindGroupEntry {
+                binding: 3,
+                resource: mask_buffer.as_entire_binding(),
+            });
+        }
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &entries,
+            label: Some("iterations_bind_group"),
+        })
+    }
+}
@@ -1,11 +1,38 @@
 pub mod camera;
+#[allow(clippy::module_inception)]
 pub mod renderer;
 pub mod shader;
 pub mod vertex;
 pub mod complex;
+pub mod bigcomplex;
+pub mod reference;
+pub mod series;
+pub mod overlay;
+pub mod animation;
+pub mod fractal;
+pub mod parameters;
+pub mod polynomial;
+pub mod sequence;
+pub mod density;
+pub mod coloring;
+pub mod palette;
+pub mod iterations;
+pub mod trap;
+pub mod window_handle;
 
 pub use renderer::Renderer;
 pub use camera::Camera;
 pub use shader::Shader;
 pub use complex::Complex;
+pub use reference::ReferenceOrbit;
+pub use overlay::Overlay;
 pub use vertex::Vertex;
+pub use parameters::Parameters;
+pub use polynomial::Polynomial;
+pub use sequence::Sequence;
+pub use density::DensityMap;
+pub use coloring::{Coloring, ColoringMode, InteriorMode};
+pub use palette::Palette;
+pub use iterations::{Iterations, HistogramMask};
+pub use trap::{OrbitTrap, TrapShape};
+pub use window_handle::WindowHandle;
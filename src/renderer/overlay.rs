@@ -0,0 +1,102 @@
+use anyhow::Result;
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+use cgmath::Vector2;
+
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct OverlayUniform {
+    /// Top-left corner of the selection, in pixels
+    min: [f32; 2],
+
+    /// Bottom-right corner of the selection, in pixels
+    max: [f32; 2],
+}
+
+/// The rubber-band selection rectangle, drawn over the fractal while the
+/// user is dragging out a zoom box (see [`super::Camera::get_selection`])
+#[derive(Debug)]
+pub struct Overlay {
+    /// Data associated with the overlay
+    uniform: OverlayUniform,
+    visible: bool,
+
+    /// WGPU objects
+    buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+impl Overlay {
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = OverlayUniform {
+            min: [0.0, 0.0],
+            max: [0.0, 0.0],
+        };
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("overlay_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("overlay_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("overlay_bind_group"),
+        });
+
+        Ok(Self {
+            uniform,
+            visible: false,
+            buffer,
+            layout,
+            group,
+        })
+    }
+
+    pub fn update(&mut self, selection: Option<(Vector2<f64>, Vector2<f64>)>, queue: &wgpu::Queue) {
+        //! Show the rectangle between the two corners in @selection, or hide
+        //! the overlay if there is no selection
+
+        self.visible = selection.is_some();
+        if let Some((a, b)) = selection {
+            self.uniform.min = [a.x.min(b.x) as f32, a.y.min(b.y) as f32];
+            self.uniform.max = [a.x.max(b.x) as f32, a.y.max(b.y) as f32];
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+}
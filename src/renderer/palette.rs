@@ -0,0 +1,503 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+
+/// A color gradient, for coloring escape-time fractals
+///
+/// Gradients are kept as a list of stops, with colors linearly interpolated
+/// between them; curved gradients (like GIMP's) are sampled into many closely
+/// spaced stops. Colors are sRGB, with each channel from 0 to 1
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// `(position, color)` pairs, sorted by position from 0 to 1
+    stops: Vec<(f64, [f64; 3])>,
+}
+
+impl Palette {
+    /// Stops that a curved gradient is sampled into
+    pub const RESOLUTION: usize = 256;
+
+    pub fn new(mut stops: Vec<(f64, [f64; 3])>) -> Result<Self> {
+        //! A gradient through the colors in @stops, which are `(position,
+        //! color)` pairs, in any order
+
+        if stops.is_empty() {
+            return Err(anyhow!("a palette needs at least one color"));
+        }
+        if stops.iter().any(|(position, color)| !position.is_finite() || color.iter().any(|c| !c.is_finite())) {
+            return Err(anyhow!("palette positions and colors must be numbers"));
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(Self { stops })
+    }
+
+    pub fn from_fn(function: impl Fn(f64) -> [f64; 3]) -> Self {
+        //! Sample @function, which maps positions from 0 to 1 to colors, into
+        //! a palette
+
+        let stops = (0..Self::RESOLUTION)
+            .map(|k| {
+                let position = k as f64 / (Self::RESOLUTION - 1) as f64;
+                (position, function(position))
+            })
+            .collect();
+
+        Self { stops }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        //! Read the gradient at @path, in a format picked by its extension:
+        //! GIMP gradients (`.ggr`), Fractint maps (`.map`), or a list of
+        //! stops (`.json`; see [`Palette::from_json`])
+
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read palette {}", path.display()))?;
+        let extension = path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("ggr") => Self::from_ggr(&source),
+            Some("map") => Self::from_map(&source),
+            Some("json") => Self::from_json(&source),
+            _ => Err(anyhow!("unknown palette format {}; expected a .ggr, .map or .json file", path.display())),
+        }
+        .with_context(|| format!("failed to load palette {}", path.display()))
+    }
+
+    pub fn from_ggr(source: &str) -> Result<Self> {
+        //! Parse a GIMP gradient: a header, the number of segments, then one
+        //! line per segment of
+        //!
+        //! `left middle right  r g b a  r g b a  blending coloring`
+        //!
+        //! with the colors at the segment's left and right ends. Blending is
+        //! how quickly the color moves from one end to the other (through the
+        //! middle point), and coloring whether it moves through RGB or HSV
+
+        let mut lines = source.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some("GIMP Gradient") {
+            return Err(anyhow!("not a GIMP gradient"));
+        }
+        let mut count = lines.next().ok_or_else(|| anyhow!("missing segment count"))?;
+        if count.starts_with("Name:") {
+            count = lines.next().ok_or_else(|| anyhow!("missing segment count"))?;
+        }
+        let count: usize = count.parse().with_context(|| format!("invalid segment count {}", count))?;
+
+        let segments = lines.take(count)
+            .map(GradientSegment::parse)
+            .collect::<Result<Vec<GradientSegment>>>()?;
+        if segments.len() != count {
+            return Err(anyhow!("expected {} segments, found {}", count, segments.len()));
+        }
+
+        Ok(Self::from_fn(|position| {
+            let segment = segments.iter()
+                .find(|segment| position <= segment.right)
+                .unwrap_or(&segments[segments.len() - 1]);
+            segment.color(position)
+        }))
+    }
+
+    pub fn from_map(source: &str) -> Result<Self> {
+        //! Parse a Fractint map: one `r g b` line (0 to 255) per color,
+        //! evenly spaced. Anything after the third number is a comment
+
+        let colors = source.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let channels = line.split_whitespace()
+                    .take(3)
+                    .map(|channel| channel.parse::<u8>().map(|c| c as f64 / 255.0))
+                    .collect::<std::result::Result<Vec<f64>, _>>()
+                    .with_context(|| format!("invalid color {}", line))?;
+                match channels[..] {
+                    [r, g, b] => Ok([r, g, b]),
+                    _ => Err(anyhow!("invalid color {}", line)),
+                }
+            })
+            .collect::<Result<Vec<[f64; 3]>>>()?;
+
+        Self::evenly_spaced(colors)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self> {
+        //! Parse a list of stops, e.g.
+        //!
+        //! `[{"position": 0, "color": "#000764"}, {"position": 1, "color": "#ffffff"}]`
+        //!
+        //! Positions go from 0 to 1. They can be left out, in which case the
+        //! colors are evenly spaced; but either every stop has one, or none
+        //! of them do
+
+        let value: serde_json::Value = serde_json::from_str(source)?;
+        let stops = value.as_array().ok_or_else(|| anyhow!("expected a list of stops"))?;
+
+        let colors = stops.iter()
+            .map(|stop| {
+                let color = stop.get("color")
+                    .and_then(|color| color.as_str())
+                    .ok_or_else(|| anyhow!("every stop needs a \"color\", like \"#ff8800\""))?;
+                Self::parse_hex(color)
+            })
+            .collect::<Result<Vec<[f64; 3]>>>()?;
+        let positions = stops.iter()
+            .map(|stop| stop.get("position").map(|position| {
+                position.as_f64().ok_or_else(|| anyhow!("invalid position {}", position))
+            }).transpose())
+            .collect::<Result<Vec<Option<f64>>>>()?;
+
+        if positions.iter().all(Option::is_none) {
+            return Self::evenly_spaced(colors);
+        }
+        let positions = positions.into_iter()
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| anyhow!("either every stop needs a \"position\", or none of them"))?;
+
+        Self::new(positions.into_iter().zip(colors).collect())
+    }
+
+    pub fn sample(&self, position: f64) -> [f64; 3] {
+        //! The color at @position, from 0 to 1
+
+        let next = self.stops.iter().position(|(stop, _)| *stop >= position);
+        match next {
+            None => self.stops[self.stops.len() - 1].1,
+            Some(0) => self.stops[0].1,
+            Some(next) => {
+                let (left, from) = self.stops[next - 1];
+                let (right, to) = self.stops[next];
+                let t = if right > left { (position - left) / (right - left) } else { 0.0 };
+                [0, 1, 2].map(|c| from[c] + (to[c] - from[c]) * t)
+            },
+        }
+    }
+
+    pub fn to_rgba8(&self, size: usize) -> Vec<[u8; 4]> {
+        //! Sample the palette at @size evenly spaced points, as sRGB bytes
+        //! (e.g. for an `Rgba8UnormSrgb` texture)
+
+        (0..size)
+            .map(|k| {
+                let color = self.sample(k as f64 / (size - 1).max(1) as f64);
+                let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                [r, g, b, 255]
+            })
+            .collect()
+    }
+
+    fn evenly_spaced(colors: Vec<[f64; 3]>) -> Result<Self> {
+        let last = (colors.len().max(2) - 1) as f64;
+        Self::new(colors.into_iter()
+            .enumerate()
+            .map(|(k, color)| (k as f64 / last, color))
+            .collect())
+    }
+
+    fn parse_hex(color: &str) -> Result<[f64; 3]> {
+        //! Parse an `#rrggbb` color
+
+        let digits = color.strip_prefix('#').unwrap_or(color);
+        if digits.len() != 6 || !digits.is_ascii() {
+            return Err(anyhow!("invalid color {}; expected #rrggbb", color));
+        }
+        let channel = |k: usize| {
+            u8::from_str_radix(&digits[2 * k..2 * k + 2], 16)
+                .map(|c| c as f64 / 255.0)
+                .with_context(|| format!("invalid color {}; expected #rrggbb", color))
+        };
+
+        Ok([channel(0)?, channel(1)?, channel(2)?])
+    }
+}
+
+/// One segment of a GIMP gradient (see [`Palette::from_ggr`])
+#[derive(Debug, Clone)]
+struct GradientSegment {
+    left: f64,
+    middle: f64,
+    right: f64,
+    left_color: [f64; 3],
+    right_color: [f64; 3],
+
+    /// 0: linear, 1: curved, 2: sinusoidal, 3: spherical (increasing),
+    /// 4: spherical (decreasing), 5: step
+    blending: u32,
+
+    /// 0: RGB, 1: HSV (hue counter-clockwise), 2: HSV (hue clockwise)
+    coloring: u32,
+}
+
+impl GradientSegment {
+    fn parse(line: &str) -> Result<Self> {
+        let values = line.split_whitespace()
+            .map(|value| value.parse::<f64>())
+            .collect::<std::result::Result<Vec<f64>, _>>()
+            .with_context(|| format!("invalid segment {}", line))?;
+        if values.len() < 13 {
+            return Err(anyhow!("invalid segment {}", line));
+        }
+
+        // the alpha channels (values[6] and values[10]) are ignored; fractals
+        // are always opaque
+        Ok(Self {
+            left: values[0],
+            middle: values[1],
+            right: values[2],
+            left_color: [values[3], values[4], values[5]],
+            right_color: [values[7], values[8], values[9]],
+            blending: values[11] as u32,
+            coloring: values[12] as u32,
+        })
+    }
+
+    fn color(&self, position: f64) -> [f64; 3] {
+        //! The color at @position within the whole gradient, following GIMP's
+        //! own blending functions
+
+        let length = self.right - self.left;
+        let (middle, position) = if length < f64::EPSILON {
+            (0.5, 0.5)
+        }
+        else {
+            ((self.middle - self.left) / length, (position - self.left) / length)
+        };
+
+        let linear = if position <= middle {
+            if middle < f64::EPSILON { 0.0 } else { 0.5 * position / middle }
+        }
+        else if middle > 1.0 - f64::EPSILON {
+            1.0
+        }
+        else {
+            0.5 + 0.5 * (position - middle) / (1.0 - middle)
+        };
+        let factor = match self.blending {
+            1 => position.max(0.0).powf(0.5f64.ln() / middle.max(f64::EPSILON).ln()),
+            2 => ((std::f64::consts::PI * linear - std::f64::consts::FRAC_PI_2).sin() + 1.0) / 2.0,
+            3 => (1.0 - (linear - 1.0).powi(2)).sqrt(),
+            4 => 1.0 - (1.0 - linear.powi(2)).sqrt(),
+            5 => if position >= middle { 1.0 } else { 0.0 },
+            _ => linear,
+        };
+
+        match self.coloring {
+            1 | 2 => {
+                let [left_h, left_s, left_v] = rgb_to_hsv(self.left_color);
+                let [right_h, right_s, right_v] = rgb_to_hsv(self.right_color);
+                let hue = if self.coloring == 1 {
+                    let delta = if left_h < right_h { right_h - left_h } else { 1.0 - (left_h - right_h) };
+                    (left_h + delta * factor).rem_euclid(1.0)
+                }
+                else {
+                    let delta = if right_h < left_h { left_h - right_h } else { 1.0 - (right_h - left_h) };
+                    (left_h - delta * factor).rem_euclid(1.0)
+                };
+                hsv_to_rgb([
+                    hue,
+                    left_s + (right_s - left_s) * factor,
+                    left_v + (right_v - left_v) * factor,
+                ])
+            },
+            _ => [0, 1, 2].map(|c| self.left_color[c] + (self.right_color[c] - self.left_color[c]) * factor),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        //! The original coloring; the hue goes around twice, getting brighter
+        //! as it goes. This is the same as `hsv_to_rgb` in `common.wgsl`,
+        //! which wrote its colors straight to an sRGB surface, so they're
+        //! encoded here to look the same
+
+        Self::from_fn(|position| {
+            let color = hsv_to_rgb([(position * 2.0).fract(), 1.0, position]);
+            color.map(linear_to_srgb)
+        })
+    }
+}
+
+fn rgb_to_hsv([r, g, b]: [f64; 3]) -> [f64; 3] {
+    //! Hue, saturation and value, all from 0 to 1
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta <= 0.0 {
+        0.0
+    }
+    else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    }
+    else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    }
+    else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let saturation = if max > 0.0 { delta / max } else { 0.0 };
+
+    [hue, saturation, max]
+}
+
+fn hsv_to_rgb([hue, saturation, value]: [f64; 3]) -> [f64; 3] {
+    let sector = (hue.rem_euclid(1.0) * 6.0).min(6.0 - f64::EPSILON);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let m = value - chroma;
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+
+    [r + m, g + m, b + m]
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    }
+    else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color(actual: [f64; 3], expected: [f64; 3]) {
+        assert!(
+            actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-9),
+            "{:?} != {:?}", actual, expected,
+        );
+    }
+
+    #[test]
+    fn ggr_with_and_without_name() {
+        let segment = "0 0.5 1  0 0 0 1  1 1 1 1  0 0";
+        for header in ["GIMP Gradient\n", "GIMP Gradient\nName: Greyscale\n"] {
+            let palette = Palette::from_ggr(&format!("{}1\n{}\n", header, segment)).unwrap();
+            assert_color(palette.sample(0.0), [0.0; 3]);
+            assert_color(palette.sample(1.0), [1.0; 3]);
+            assert_color(palette.sample(0.5), [0.5; 3]);
+        }
+    }
+
+    #[test]
+    fn ggr_picks_the_segment() {
+        let source = "GIMP Gradient\n2\n\
+            0 0.25 0.5  1 0 0 1  1 0 0 1  0 0\n\
+            0.5 0.75 1  0 0 1 1  0 0 1 1  0 0\n";
+        let palette = Palette::from_ggr(source).unwrap();
+        assert_color(palette.sample(0.25), [1.0, 0.0, 0.0]);
+        assert_color(palette.sample(0.75), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn ggr_segment_count_mismatch() {
+        let source = "GIMP Gradient\n2\n0 0.5 1  0 0 0 1  1 1 1 1  0 0\n";
+        let error = Palette::from_ggr(source).unwrap_err();
+        assert_eq!(error.to_string(), "expected 2 segments, found 1");
+    }
+
+    #[test]
+    fn ggr_rejects_other_files() {
+        assert!(Palette::from_ggr("1\n0 0.5 1  0 0 0 1  1 1 1 1  0 0\n").is_err());
+        assert!(Palette::from_ggr("GIMP Gradient\n1\n0 0.5 1  0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn segment_color() {
+        let mut segment = GradientSegment {
+            left: 0.5,
+            middle: 0.75,
+            right: 1.0,
+            left_color: [1.0, 0.0, 0.0],
+            right_color: [0.0, 0.0, 1.0],
+            blending: 0,
+            coloring: 0,
+        };
+        assert_color(segment.color(0.5), [1.0, 0.0, 0.0]);
+        assert_color(segment.color(0.75), [0.5, 0.0, 0.5]);
+        assert_color(segment.color(1.0), [0.0, 0.0, 1.0]);
+
+        // a step switches at the middle point
+        segment.blending = 5;
+        assert_color(segment.color(0.7), [1.0, 0.0, 0.0]);
+        assert_color(segment.color(0.8), [0.0, 0.0, 1.0]);
+
+        // red to blue counter-clockwise goes through green, and clockwise
+        // through magenta
+        segment.blending = 0;
+        segment.coloring = 1;
+        assert_color(segment.color(0.75), [0.0, 1.0, 0.0]);
+        segment.coloring = 2;
+        assert_color(segment.color(0.75), [1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn map_ignores_comments() {
+        let source = "0 0 0 black\n\n255 255 255  white, with more words\n";
+        let palette = Palette::from_map(source).unwrap();
+        assert_color(palette.sample(0.0), [0.0; 3]);
+        assert_color(palette.sample(1.0), [1.0; 3]);
+    }
+
+    #[test]
+    fn map_rejects_bad_colors() {
+        assert!(Palette::from_map("0 0\n").is_err());
+        assert!(Palette::from_map("0 0 256\n").is_err());
+        assert!(Palette::from_map("").is_err());
+    }
+
+    #[test]
+    fn json_with_positions() {
+        let source = r##"[
+            {"position": 1, "color": "#ffffff"},
+            {"position": 0.2, "color": "#000000"}
+        ]"##;
+        let palette = Palette::from_json(source).unwrap();
+        assert_color(palette.sample(0.0), [0.0; 3]);
+        assert_color(palette.sample(0.2), [0.0; 3]);
+        assert_color(palette.sample(0.6), [0.5; 3]);
+    }
+
+    #[test]
+    fn json_without_positions() {
+        let source = r##"[{"color": "#ff0000"}, {"color": "#00ff00"}, {"color": "#0000ff"}]"##;
+        let palette = Palette::from_json(source).unwrap();
+        assert_color(palette.sample(0.0), [1.0, 0.0, 0.0]);
+        assert_color(palette.sample(0.5), [0.0, 1.0, 0.0]);
+        assert_color(palette.sample(0.75), [0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn json_rejects_bad_stops() {
+        assert!(Palette::from_json(r##"{"color": "#ffffff"}"##).is_err());
+        assert!(Palette::from_json(r##"[{"position": 0}]"##).is_err());
+        assert!(Palette::from_json(r##"[{"position": "start", "color": "#ffffff"}]"##).is_err());
+    }
+
+    #[test]
+    fn json_rejects_partial_positions() {
+        let source = r##"[{"position": 0.9, "color": "#000000"}, {"color": "#ffffff"}]"##;
+        let error = Palette::from_json(source).unwrap_err();
+        assert_eq!(error.to_string(), "either every stop needs a \"position\", or none of them");
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_color(Palette::parse_hex("#ff8000").unwrap(), [1.0, 128.0 / 255.0, 0.0]);
+        assert_color(Palette::parse_hex("00FF00").unwrap(), [0.0, 1.0, 0.0]);
+        for color in ["#fff", "#ff80000", "#gg0000", "#ff80é", ""] {
+            assert!(Palette::parse_hex(color).is_err(), "{}", color);
+        }
+    }
+}
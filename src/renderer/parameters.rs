@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Result};
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+use super::fractal::Parameter;
+
+/// The GPU-side view of [`Parameters`]. WGSL pads uniform arrays out to 16
+/// bytes per element, so the values are packed four to a `vec4`
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ParametersUniform {
+    values: [[f32; 4]; Parameters::MAX / 4],
+}
+
+/// A parameter sweeping back and forth between two values, e.g. to show a
+/// Multibrot morphing between powers
+#[derive(Debug, Clone)]
+pub struct ParameterAnimation {
+    /// Index of the parameter being animated
+    index: usize,
+    from: f32,
+    to: f32,
+
+    /// Seconds to get from @from to @to (and the same again to get back)
+    duration: f64,
+
+    /// Seconds since the animation started
+    elapsed: f64,
+}
+
+impl ParameterAnimation {
+    pub fn current(&self) -> f32 {
+        //! The parameter's value at this point in the animation
+
+        let t = (self.elapsed / self.duration) % 2.0;
+        let t = if t > 1.0 { 2.0 - t } else { t };
+        self.from + (self.to - self.from) * t as f32
+    }
+}
+
+/// Current values of the active fractal's parameters (see
+/// [`super::fractal::Fractal::parameters`]), shared by every camera
+#[derive(Debug)]
+pub struct Parameters {
+    /// The parameters being set, and their current values
+    parameters: &'static [Parameter],
+    uniform: ParametersUniform,
+    needs_upload: bool,
+
+    /// Parameters that are currently animating
+    animations: Vec<ParameterAnimation>,
+
+    /// WGPU objects
+    buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+impl Parameters {
+    /// Most parameters a single fractal can have
+    pub const MAX: usize = 16;
+
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = ParametersUniform {
+            values: [[0.0; 4]; Self::MAX / 4],
+        };
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("parameters_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("parameters_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("parameters_bind_group"),
+        });
+
+        Ok(Self {
+            parameters: &[],
+            uniform,
+            needs_upload: false,
+            animations: Vec::new(),
+            buffer,
+            layout,
+            group,
+        })
+    }
+
+    pub fn reset(&mut self, parameters: &'static [Parameter]) {
+        //! Switch to a new list of @parameters, each at its default value
+
+        self.parameters = parameters;
+        self.animations.clear();
+        self.uniform.values = [[0.0; 4]; Self::MAX / 4];
+        for (index, parameter) in parameters.iter().take(Self::MAX).enumerate() {
+            self.uniform.values[index / 4][index % 4] = parameter.default;
+        }
+        self.needs_upload = true;
+    }
+
+    pub fn set(&mut self, name: &str, value: f32) -> Result<()> {
+        //! Set the parameter called @name to @value, stopping any animation
+
+        let index = self.find(name)?;
+        self.animations.retain(|animation| animation.index != index);
+        self.set_index(index, value);
+
+        Ok(())
+    }
+
+    pub fn animate(&mut self, name: &str, from: f32, to: f32, duration: f64) -> Result<()> {
+        //! Sweep the parameter called @name from @from to @to over @duration
+        //! seconds, then back again, until it's set to something else
+
+        let index = self.find(name)?;
+        self.animations.retain(|animation| animation.index != index);
+        self.animations.push(ParameterAnimation {
+            index,
+            from,
+            to,
+            duration: duration.max(f64::EPSILON),
+            elapsed: 0.0,
+        });
+        self.set_index(index, from);
+
+        Ok(())
+    }
+
+    pub fn advance(&mut self, dt: f64) {
+        //! Move any animating parameters on by @dt seconds
+
+        for animation in &mut self.animations {
+            animation.elapsed += dt;
+            self.uniform.values[animation.index / 4][animation.index % 4] = animation.current();
+            self.needs_upload = true;
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) -> bool {
+        //! Upload the values if any have changed, and return whether they had
+
+        if self.needs_upload {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            self.needs_upload = false;
+            return true;
+        }
+
+        false
+    }
+
+    fn find(&self, name: &str) -> Result<usize> {
+        self.parameters.iter()
+            .position(|parameter| parameter.name == name)
+            .ok_or_else(|| anyhow!("unknown parameter {}", name))
+    }
+
+    fn set_index(&mut self, index: usize, value: f32) {
+        self.uniform.values[index / 4][index % 4] = value;
+        self.needs_upload = true;
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+}
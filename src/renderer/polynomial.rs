@@ -0,0 +1,341 @@
+use anyhow::{anyhow, Result};
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+use super::Complex;
+
+/// The GPU-side view of [`Polynomial`]; the coefficients and roots
+/// themselves are in storage buffers
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct PolynomialUniform {
+    /// Degree of the polynomial; there is one more coefficient than this
+    degree: u32,
+
+    /// Number of roots in the roots buffer
+    roots: u32,
+
+    _padding: [u32; 2],
+}
+
+/// A polynomial `p(z)`, for Newton's method fractals
+///
+/// The shader needs the coefficients, to evaluate `p(z)` and `p'(z)`, and the
+/// roots, to tell which one each pixel converged to. Either can be given;
+/// the other is worked out here
+#[derive(Debug)]
+pub struct Polynomial {
+    /// Coefficients, lowest order first
+    coefficients: Vec<Complex>,
+    roots: Vec<Complex>,
+
+    /// Data uploaded to the GPU
+    uniform: PolynomialUniform,
+    needs_upload: bool,
+
+    /// WGPU objects
+    uniform_buffer: wgpu::Buffer,
+    coefficients_buffer: wgpu::Buffer,
+    roots_buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+impl Polynomial {
+    /// Highest degree polynomial that fits in the buffers
+    pub const MAX_DEGREE: usize = 16;
+
+    /// How many rounds of root-finding to try, at most
+    pub const MAX_ROOT_ITERATIONS: usize = 1000;
+
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = PolynomialUniform {
+            degree: 0,
+            roots: 0,
+            _padding: [0; 2],
+        };
+        let uniform_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("polynomial_uniform_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let coefficients_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("polynomial_coefficients_buffer"),
+            size: (Self::MAX_DEGREE + 1) as u64 * std::mem::size_of::<[f32; 2]>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let roots_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("polynomial_roots_buffer"),
+            size: Self::MAX_DEGREE as u64 * std::mem::size_of::<[f32; 2]>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("polynomial_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: coefficients_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: roots_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("polynomial_bind_group"),
+        });
+
+        let mut polynomial = Self {
+            coefficients: Vec::new(),
+            roots: Vec::new(),
+            uniform,
+            needs_upload: false,
+            uniform_buffer,
+            coefficients_buffer,
+            roots_buffer,
+            layout,
+            group,
+        };
+
+        // z^3 - 1; the classic three-way Newton fractal
+        polynomial.set_coefficients(&[
+            Complex::new(-1.0, 0.0),
+            Complex::new(0.0, 0.0),
+            Complex::new(0.0, 0.0),
+            Complex::new(1.0, 0.0),
+        ])?;
+
+        Ok(polynomial)
+    }
+
+    pub fn set_roots(&mut self, roots: &[Complex]) -> Result<()> {
+        //! Use the (monic) polynomial with the given @roots, i.e.
+        //! `(z - r_1)(z - r_2)...(z - r_n)`
+
+        if roots.is_empty() || roots.len() > Self::MAX_DEGREE {
+            return Err(anyhow!("a polynomial needs between 1 and {} roots", Self::MAX_DEGREE));
+        }
+
+        self.coefficients = Self::expand_roots(roots);
+        self.roots = roots.to_vec();
+        self.update_uniform();
+
+        Ok(())
+    }
+
+    pub fn set_coefficients(&mut self, coefficients: &[Complex]) -> Result<()> {
+        //! Use the polynomial with the given @coefficients, lowest order
+        //! first, and find its roots
+
+        let mut coefficients = coefficients.to_vec();
+        while coefficients.last().is_some_and(|c| c.norm_sqr() == 0.0) {
+            coefficients.pop();
+        }
+        if coefficients.len() < 2 || coefficients.len() > Self::MAX_DEGREE + 1 {
+            return Err(anyhow!("a polynomial needs a degree between 1 and {}", Self::MAX_DEGREE));
+        }
+
+        self.roots = Self::find_roots(&coefficients);
+        self.coefficients = coefficients;
+        self.update_uniform();
+
+        Ok(())
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) {
+        if self.needs_upload {
+            let coefficients: Vec<[f32; 2]> = self.coefficients.iter().map(|c| c.to_f32()).collect();
+            let roots: Vec<[f32; 2]> = self.roots.iter().map(|c| c.to_f32()).collect();
+            queue.write_buffer(&self.coefficients_buffer, 0, bytemuck::cast_slice(&coefficients));
+            queue.write_buffer(&self.roots_buffer, 0, bytemuck::cast_slice(&roots));
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            self.needs_upload = false;
+        }
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+
+    fn update_uniform(&mut self) {
+        self.uniform.degree = self.coefficients.len() as u32 - 1;
+        self.uniform.roots = self.roots.len() as u32;
+        self.needs_upload = true;
+    }
+
+    fn evaluate(coefficients: &[Complex], z: Complex) -> Complex {
+        //! `p(z)`, using Horner's method
+
+        coefficients.iter().rev().fold(Complex::new(0.0, 0.0), |p, c| p * z + *c)
+    }
+
+    fn expand_roots(roots: &[Complex]) -> Vec<Complex> {
+        //! The coefficients of the monic polynomial with @roots, lowest order
+        //! first. This multiplies out one `(z - r)` at a time
+
+        let mut coefficients = vec![Complex::new(1.0, 0.0)];
+        for root in roots {
+            let mut next = vec![Complex::new(0.0, 0.0); coefficients.len() + 1];
+            for (k, coefficient) in coefficients.iter().enumerate() {
+                next[k + 1] = next[k + 1] + *coefficient;
+                next[k] = next[k] - *coefficient * *root;
+            }
+            coefficients = next;
+        }
+
+        coefficients
+    }
+
+    fn find_roots(coefficients: &[Complex]) -> Vec<Complex> {
+        //! Find every root of the polynomial with @coefficients at once, with
+        //! the Durand-Kerner method. Each guess is pushed towards a root by a
+        //! Newton-like step, and away from all the other guesses, so that
+        //! they all settle on different roots
+
+        let leading = *coefficients.last().unwrap();
+        let monic: Vec<Complex> = coefficients.iter().map(|c| *c / leading).collect();
+        let degree = monic.len() - 1;
+
+        // the starting guesses just need to be distinct, and not symmetric
+        let seed = Complex::new(0.4, 0.9);
+        let mut roots = vec![Complex::new(1.0, 0.0)];
+        for k in 1..degree {
+            roots.push(roots[k - 1] * seed);
+        }
+
+        for _ in 0..Self::MAX_ROOT_ITERATIONS {
+            let mut largest_step: f64 = 0.0;
+            for i in 0..degree {
+                let denominator = (0..degree)
+                    .filter(|j| *j != i)
+                    .fold(Complex::new(1.0, 0.0), |d, j| d * (roots[i] - roots[j]));
+                let step = Self::evaluate(&monic, roots[i]) / denominator;
+                roots[i] = roots[i] - step;
+                largest_step = largest_step.max(step.norm_sqr());
+            }
+            if largest_step < 1e-24 {
+                break;
+            }
+        }
+
+        roots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_roots(found: &[Complex], expected: &[Complex]) {
+        //! Every root in @expected was found, in any order
+
+        assert_eq!(found.len(), expected.len());
+        let mut unmatched = found.to_vec();
+        for root in expected {
+            let closest = unmatched.iter()
+                .enumerate()
+                .min_by(|a, b| (*a.1 - *root).norm_sqr().total_cmp(&(*b.1 - *root).norm_sqr()))
+                .map(|(k, _)| k)
+                .unwrap();
+            let found = unmatched.remove(closest);
+            assert!((found - *root).norm_sqr() < 1e-18, "{:?} != {:?}", found, root);
+        }
+    }
+
+    #[test]
+    fn expands_roots() {
+        // (z - 1)(z + 1) = z^2 - 1
+        let coefficients = Polynomial::expand_roots(&[Complex::new(1.0, 0.0), Complex::new(-1.0, 0.0)]);
+        let coefficients: Vec<(f64, f64)> = coefficients.iter().map(|c| (c.re, c.im)).collect();
+        assert_eq!(coefficients, vec![(-1.0, 0.0), (0.0, 0.0), (1.0, 0.0)]);
+    }
+
+    #[test]
+    fn finds_roots_of_unity() {
+        let coefficients = [
+            Complex::new(-1.0, 0.0),
+            Complex::new(0.0, 0.0),
+            Complex::new(0.0, 0.0),
+            Complex::new(1.0, 0.0),
+        ];
+        let (sin, cos) = (std::f64::consts::TAU / 3.0).sin_cos();
+        assert_same_roots(&Polynomial::find_roots(&coefficients), &[
+            Complex::new(1.0, 0.0),
+            Complex::new(cos, sin),
+            Complex::new(cos, -sin),
+        ]);
+    }
+
+    #[test]
+    fn roots_round_trip() {
+        let roots = [
+            Complex::new(0.5, -1.25),
+            Complex::new(-2.0, 0.0),
+            Complex::new(0.0, 1.0),
+            Complex::new(1.5, 0.75),
+            Complex::new(-0.25, -0.5),
+        ];
+        let coefficients = Polynomial::expand_roots(&roots);
+        let found = Polynomial::find_roots(&coefficients);
+        assert_same_roots(&found, &roots);
+        for root in found {
+            assert!(Polynomial::evaluate(&coefficients, root).norm_sqr() < 1e-18);
+        }
+    }
+
+    #[test]
+    fn finds_roots_of_non_monic_polynomials() {
+        // 2(z - i)(z + 3)
+        let coefficients: Vec<Complex> = Polynomial::expand_roots(&[Complex::new(0.0, 1.0), Complex::new(-3.0, 0.0)])
+            .into_iter()
+            .map(|c| c * Complex::new(2.0, 0.0))
+            .collect();
+        assert_same_roots(&Polynomial::find_roots(&coefficients), &[Complex::new(0.0, 1.0), Complex::new(-3.0, 0.0)]);
+    }
+}
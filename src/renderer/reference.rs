@@ -0,0 +1,330 @@
+use anyhow::Result;
+use bytemuck;
+use std::sync::mpsc;
+use std::thread;
+use wgpu;
+use wgpu::util::DeviceExt;
+use super::bigcomplex::BigComplex;
+use super::camera::{Camera, CameraState};
+use super::series::SeriesApproximation;
+
+/// The GPU-side view of [`ReferenceOrbit`]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ReferenceUniform {
+    /// Offset from the reference point to the camera origin. Each pixel's
+    /// offset from the reference is this, plus its offset from the origin
+    offset_re: f32,
+    offset_im: f32,
+
+    /// Number of points in the orbit buffer
+    length: u32,
+
+    /// Iteration limit for each pixel
+    max_iterations: u32,
+
+    /// Iterations skipped by the series approximation (see
+    /// [`SeriesApproximation`])
+    series_skip: u32,
+
+    /// Number of series coefficients in the series buffer
+    series_terms: u32,
+
+    /// The radius the series coefficients are scaled by
+    series_radius: f32,
+
+    _padding: u32,
+}
+
+/// A high-precision orbit, used for perturbation rendering at deep zoom
+///
+/// The orbit `Z_n` of a single reference point `C` is iterated on the CPU,
+/// with enough precision for the current zoom level. The shader then only
+/// needs to iterate each pixel's (small) offset from that orbit, which `f32`
+/// can do accurately:
+///
+/// `dz_{n+1} = 2 * Z_n * dz_n + dz_n^2 + dc`
+///
+/// At deep zooms, the orbit can take a long time to calculate, so it's done
+/// on a worker thread. The previous orbit is used until the new one is ready
+#[derive(Debug)]
+pub struct ReferenceOrbit {
+    /// The reference point `C`; `None` until the first orbit is ready
+    center: Option<BigComplex>,
+
+    /// The orbit being calculated on a worker thread, if any
+    pending: Option<mpsc::Receiver<Calculation>>,
+
+    /// Data uploaded to the GPU
+    uniform: ReferenceUniform,
+
+    /// WGPU objects
+    uniform_buffer: wgpu::Buffer,
+    orbit_buffer: wgpu::Buffer,
+    series_buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+/// A reference orbit and its series approximation, calculated by
+/// [`ReferenceOrbit::calculate`], ready to upload
+#[derive(Debug)]
+struct Calculation {
+    center: BigComplex,
+    max_iterations: u32,
+    orbit: Vec<[f32; 2]>,
+    series: Vec<[f32; 2]>,
+    series_skip: u32,
+    series_radius: f32,
+}
+
+impl ReferenceOrbit {
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = ReferenceUniform {
+            offset_re: 0.0,
+            offset_im: 0.0,
+            length: 1,
+            max_iterations: 0,
+            series_skip: 0,
+            series_terms: 0,
+            series_radius: 1.0,
+            _padding: 0,
+        };
+        let uniform_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("reference_uniform_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let orbit_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("reference_orbit_buffer"),
+            size: (CameraState::MAX_ITERATIONS as usize + 1) as u64 * std::mem::size_of::<[f32; 2]>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let series_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("reference_series_buffer"),
+            size: SeriesApproximation::TERMS as u64 * std::mem::size_of::<[f32; 2]>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("reference_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: orbit_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: series_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("reference_bind_group"),
+        });
+
+        Ok(Self {
+            center: None,
+            pending: None,
+            uniform,
+            uniform_buffer,
+            orbit_buffer,
+            series_buffer,
+            layout,
+            group,
+        })
+    }
+
+    pub fn update(&mut self, camera: &Camera, queue: &wgpu::Queue) -> bool {
+        //! Start re-calculating the orbit if the camera has moved too far
+        //! from the reference point (or zoomed in past its precision), pick
+        //! up the new orbit if it's ready, and upload the camera's current
+        //! offset from the reference point. Returns whether a new orbit was
+        //! picked up, in which case the fractal has to be iterated again
+
+        let origin = camera.get_origin();
+        let max_iterations = camera.get_max_iterations();
+        let view_size = camera.get_pixel_size() * camera.get_width().max(camera.get_height());
+
+        let mut changed = false;
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(calculation) => {
+                    self.install(calculation, queue);
+                    self.pending = None;
+                    changed = true;
+                },
+                Err(mpsc::TryRecvError::Empty) => {},
+                // the worker died without an answer; start over
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        let stale = match &self.center {
+            None => true,
+            Some(center) => {
+                let offset = (origin.clone() - center.clone()).to_complex();
+                center.bits() < origin.bits()
+                    || max_iterations != self.uniform.max_iterations
+                    || offset.norm_sqr().sqrt() > view_size
+            }
+        };
+        // only one orbit is calculated at a time; if the view has moved on
+        // by the time it's ready, it'll be stale, and the next one starts
+        if stale && self.pending.is_none() {
+            self.pending = Some(Self::spawn(origin.clone(), max_iterations, view_size));
+        }
+
+        if let Some(center) = &self.center {
+            let offset = (origin.clone() - center.clone()).to_complex();
+            self.uniform.offset_re = offset.re as f32;
+            self.uniform.offset_im = offset.im as f32;
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+
+        changed
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+
+    fn spawn(center: BigComplex, max_iterations: u32, view_size: f64) -> mpsc::Receiver<Calculation> {
+        //! Start calculating the orbit of @center on a worker thread; the
+        //! result arrives on the returned channel
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // if the receiver's gone, nobody needs the orbit any more
+            let _ = sender.send(Self::calculate(center, max_iterations, view_size));
+        });
+
+        receiver
+    }
+
+    fn calculate(center: BigComplex, max_iterations: u32, view_size: f64) -> Calculation {
+        //! Iterate `Z_{n+1} = Z_n^2 + C` at full precision, until @center
+        //! escapes or @max_iterations is reached, then fit a series
+        //! approximation to the orbit
+        //!
+        //! The shader rebases each pixel back to the start of the orbit once
+        //! it runs off the end, so an escaping reference is still usable
+
+        let mut z = BigComplex::zero(center.bits());
+        let mut points = vec![z.to_complex()];
+        for _ in 0..max_iterations {
+            z = z.sqr() + center.clone();
+            let point = z.to_complex();
+            points.push(point);
+            if point.norm_sqr() > 4.0 {
+                break;
+            }
+        }
+
+        // the orbit is re-used until the camera moves one view away from the
+        // reference point, so the series has to cover a bit more than that
+        let series = SeriesApproximation::new(&points, 2.0 * view_size);
+
+        Calculation {
+            center,
+            max_iterations,
+            orbit: points.iter().map(|c| c.to_f32()).collect(),
+            series: series.get_coefficients().iter().map(|c| c.to_f32()).collect(),
+            series_skip: series.get_skip(),
+            series_radius: series.get_radius() as f32,
+        }
+    }
+
+    fn install(&mut self, calculation: Calculation, queue: &wgpu::Queue) {
+        //! Switch to the newly calculated orbit
+
+        queue.write_buffer(&self.orbit_buffer, 0, bytemuck::cast_slice(&calculation.orbit));
+        queue.write_buffer(&self.series_buffer, 0, bytemuck::cast_slice(&calculation.series));
+        self.center = Some(calculation.center);
+        self.uniform.length = calculation.orbit.len() as u32;
+        self.uniform.max_iterations = calculation.max_iterations;
+        self.uniform.series_skip = calculation.series_skip;
+        self.uniform.series_terms = calculation.series.len() as u32;
+        self.uniform.series_radius = calculation.series_radius;
+        log::info!(
+            "reference orbit: {} points, series approximation skipped {} of {} iterations",
+            self.uniform.length,
+            self.uniform.series_skip,
+            self.uniform.max_iterations,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::complex::Complex;
+
+    #[test]
+    fn orbit_matches_f64() {
+        // inside the main cardioid, so the orbit runs to the limit
+        let c = Complex::new(-0.1, 0.2);
+        let calculation = ReferenceOrbit::calculate(BigComplex::from_complex(&c, 64), 100, 1e-3);
+        assert_eq!(calculation.orbit.len(), 101);
+
+        let mut z = Complex::new(0.0, 0.0);
+        for point in &calculation.orbit {
+            assert_eq!(*point, z.to_f32());
+            z = z * z + c;
+        }
+        assert!(calculation.series_skip > 0);
+        assert!((calculation.series_skip as usize) < calculation.orbit.len());
+    }
+
+    #[test]
+    fn orbit_stops_once_escaped() {
+        // 0, 1, 2, 5
+        let c = BigComplex::from_complex(&Complex::new(1.0, 0.0), 64);
+        let calculation = ReferenceOrbit::calculate(c, 100, 1e-3);
+        assert_eq!(calculation.orbit, vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [5.0, 0.0]]);
+    }
+}
@@ -1,35 +1,162 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use bytemuck;
 use winit;
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
 use wgpu;
 use wgpu::util::DeviceExt;
+use std::path::Path;
 use std::time::Duration;
 use super::Shader;
 use super::Camera;
 use super::Vertex;
 use super::Complex;
+use super::ReferenceOrbit;
+use super::Overlay;
+use super::Parameters;
+use super::Polynomial;
+use super::Sequence;
+use super::DensityMap;
+use super::{Coloring, ColoringMode, InteriorMode};
+use super::Palette;
+use super::{Iterations, HistogramMask};
+use super::WindowHandle;
+use super::TrapShape;
+use super::fractal::{self, Fractal, Resource};
+
+/// Which flavour of arithmetic the fractal shader iterates with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// Plain `f32`; fast, but breaks down at around 2^13 zoom
+    Single,
+
+    /// Emulated double precision, using pairs of `f32`s (see `df64.wgsl`)
+    Double,
+
+    /// Perturbation against a high-precision reference orbit (see
+    /// [`ReferenceOrbit`]); usable down to [`CameraState::MAX_ZOOM`]
+    ///
+    /// [`CameraState::MAX_ZOOM`]: super::camera::CameraState::MAX_ZOOM
+    Perturbation,
+}
+
+impl Precision {
+    /// Zoom level at which [`Precision::Single`] starts to run out of bits
+    pub const DOUBLE_ZOOM: f64 = 4096.0;
+    /// Zoom level at which [`Precision::Double`] starts to run out of bits
+    pub const PERTURBATION_ZOOM: f64 = 17179869184.0;
+    pub const ALL: [Precision; 3] = [Precision::Single, Precision::Double, Precision::Perturbation];
+
+    pub fn for_zoom(zoom: f64) -> Self {
+        //! Pick the cheapest precision that can still render @zoom cleanly
+
+        if zoom < Self::DOUBLE_ZOOM {
+            Precision::Single
+        }
+        else if zoom < Self::PERTURBATION_ZOOM {
+            Precision::Double
+        }
+        else {
+            Precision::Perturbation
+        }
+    }
+
+}
+
+/// Which plane is being explored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The parameter plane (e.g. the Mandelbrot set), where each pixel is a
+    /// `c`
+    Mandelbrot,
+
+    /// A Julia set; the dynamic plane for a single `c`, picked from the
+    /// parameter plane (see [`Renderer::toggle_julia`])
+    Julia,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 2] = [Mode::Mandelbrot, Mode::Julia];
+}
+
+/// Pipelines (and their shaders) are built for each supported combination
+type PipelineKey = (Mode, Precision);
 
 // A rect that covers the entire screen space (-1,-1 to 1,1)
 
 pub struct Renderer {
-    instance: wgpu::Instance,
+    _instance: wgpu::Instance,
     surface: wgpu::Surface,
-    adapter: wgpu::Adapter,
+    _adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
 
-    pipeline: wgpu::RenderPipeline,
+    /// Every fractal that can be switched to, and which one is showing
+    fractals: Vec<Box<dyn Fractal>>,
+    fractal: usize,
+    parameters: Parameters,
+    coloring: Coloring,
+
+    /// Escape-time results for fractals colored with the palette, which
+    /// only need rendering again when the view changes, and the pass that
+    /// colors them every frame. The preview has its own flag, as it moves
+    /// with the cursor
+    iterations: Iterations,
+    needs_iteration: bool,
+    preview_needs_iteration: bool,
+    coloring_pipeline: wgpu::RenderPipeline,
+    _coloring_shader: Shader,
 
-    shader: Shader,
+    /// The compute pipelines that build the histogram of the escape-time
+    /// results, for histogram coloring (see `histogram.wgsl`)
+    histogram_clear_pipeline: wgpu::ComputePipeline,
+    histogram_range_pipeline: wgpu::ComputePipeline,
+    histogram_count_pipeline: wgpu::ComputePipeline,
+    histogram_prefix_pipeline: wgpu::ComputePipeline,
+    _histogram_shader: Shader,
+
+    /// Pipelines for the current fractal; rebuilt when switching fractals
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    precision: Precision,
+    mode: Mode,
+
+    _shaders: HashMap<PipelineKey, Shader>,
     camera: Camera,
+    julia_camera: Camera,
+    reference: ReferenceOrbit,
+    polynomial: Polynomial,
+    sequence: Sequence,
     vertex_buffer: wgpu::Buffer,
+
+    /// Orbit counts for density fractals, and the compute pipelines that
+    /// accumulate (and clear) them
+    density: DensityMap,
+    density_pipeline: wgpu::ComputePipeline,
+    density_clear_pipeline: wgpu::ComputePipeline,
+    _density_shader: Shader,
+
+    /// The picture-in-picture Julia set for the point under the cursor,
+    /// shown while exploring the Mandelbrot set
+    preview_camera: Camera,
+    preview_visible: bool,
+
+    overlay: Overlay,
+    overlay_pipeline: wgpu::RenderPipeline,
+    _overlay_shader: Shader,
 }
 
 impl Renderer {
-    pub const DEFAULT_CAMERA_SCALE: f32 = 3.0;
-    pub const DEFAULT_CAMERA_ORIGIN: Complex = Complex { re: -0.5, im: 0.0 };
-    pub const DEFAULT_SHADER: &'static str = "./shaders/mandelbrot.wgsl";
+    pub const OVERLAY_SHADER: &'static str = "./shaders/overlay.wgsl";
+    pub const COLORING_SHADER: &'static str = "./shaders/coloring.wgsl";
+
+    /// Size of the Julia preview inset, as a fraction of the window's
+    /// shorter side
+    pub const PREVIEW_SIZE: f64 = 0.3;
+
+    /// Gap between the Julia preview inset and the corner of the window, in
+    /// pixels
+    pub const PREVIEW_MARGIN: f64 = 16.0;
     const VERTICES: [Vertex; 6] = [
         Vertex { position: [-1.0, -1.0, 0.0] },
         Vertex { position: [ 1.0, -1.0, 0.0] },
@@ -40,10 +167,15 @@ impl Renderer {
         Vertex { position: [ 1.0,  1.0, 0.0] },
     ];
 
-    pub async fn new(window: &winit::window::Window, scale: Option<f32>, origin: Option<Complex>) -> Result<Self> {
-        let scale = scale.unwrap_or(Self::DEFAULT_CAMERA_SCALE);
-        let origin = origin.unwrap_or(Self::DEFAULT_CAMERA_ORIGIN);
-        let shader_path = Self::DEFAULT_SHADER;
+    pub async fn new(window: &winit::window::Window, fractal_name: Option<&str>) -> Result<Self> {
+        //! Create a renderer for @window, showing the fractal called
+        //! @fractal_name, or the first registered fractal if `None`
+
+        let fractals = fractal::registry();
+        let fractal = match fractal_name {
+            Some(name) => Self::find_fractal(&fractals, name)?,
+            None => 0,
+        };
         let size = window.inner_size();
 
         let (
@@ -53,27 +185,97 @@ impl Renderer {
             device,
             queue,
             config,
-        ) = Self::init_device(&window).await?;
+        ) = Self::init_device(window).await?;
 
-        let shader = Shader::new(&device, shader_path)?;
-        let camera = Camera::new(&device, size.width as f32, size.height as f32, scale, origin)?;
+        let current = &fractals[fractal];
+        let camera = Camera::new(&device, size.width as f64, size.height as f64, current.default_scale(), current.default_origin())?;
+        let julia_camera = Camera::new(&device, size.width as f64, size.height as f64, current.julia_scale(), current.julia_origin())?;
+        let mut preview_camera = Camera::new(&device, size.width as f64, size.height as f64, current.julia_scale(), current.julia_origin())?;
+        let (preview_x, preview_y, preview_size) = Self::preview_rect(size.width as f64, size.height as f64);
+        preview_camera.set_viewport(preview_x, preview_y, preview_size, preview_size);
+        let reference = ReferenceOrbit::new(&device)?;
+        let polynomial = Polynomial::new(&device)?;
+        let sequence = Sequence::new(&device)?;
         let vertex_buffer = Self::init_vertex_buffer(&device)?;
-        let render_pipeline = Self::init_pipeline(&device, &config, &shader, &camera)?;
 
-        Ok(Self {
-            instance,
+        let mut parameters = Parameters::new(&device)?;
+        parameters.reset(current.parameters());
+        let mode = Mode::Mandelbrot;
+        let precision = Self::precision_for_zoom(current.as_ref(), mode, camera.get_zoom());
+        let coloring = Coloring::new(&device)?;
+
+        let iterations = Iterations::new(&device, size.width, size.height)?;
+        let coloring_shader = Shader::new(&device, Self::COLORING_SHADER)?;
+        let coloring_layouts = [camera.get_layout(), parameters.get_layout(), coloring.get_layout(), iterations.get_layout()];
+        let coloring_pipeline = Self::init_pipeline(&device, config.format, &coloring_shader, "fs_main", &coloring_layouts, Some(wgpu::BlendState::REPLACE))?;
+        let histogram_shader = Shader::new(&device, Iterations::HISTOGRAM_SHADER)?;
+        let histogram_layouts = [camera.get_layout(), parameters.get_layout(), coloring.get_layout(), iterations.get_compute_layout()];
+        let histogram_clear_pipeline = Self::init_compute_pipeline(&device, &histogram_shader, "cs_clear", &histogram_layouts)?;
+        let histogram_range_pipeline = Self::init_compute_pipeline(&device, &histogram_shader, "cs_range", &histogram_layouts)?;
+        let histogram_count_pipeline = Self::init_compute_pipeline(&device, &histogram_shader, "cs_count", &histogram_layouts)?;
+        let histogram_prefix_pipeline = Self::init_compute_pipeline(&device, &histogram_shader, "cs_prefix", &histogram_layouts)?;
+
+        let density = DensityMap::new(&device, size.width, size.height)?;
+        let density_shader = Shader::new(&device, DensityMap::SHADER)?;
+        let density_layouts = [camera.get_layout(), parameters.get_layout(), coloring.get_layout(), density.get_compute_layout()];
+        let density_pipeline = Self::init_compute_pipeline(&device, &density_shader, "cs_accumulate", &density_layouts)?;
+        let density_clear_pipeline = Self::init_compute_pipeline(&device, &density_shader, "cs_clear", &density_layouts)?;
+
+        let overlay = Overlay::new(&device)?;
+        let overlay_shader = Shader::new(&device, Self::OVERLAY_SHADER)?;
+        let overlay_pipeline = Self::init_pipeline(&device, config.format, &overlay_shader, "fs_main", &[overlay.get_layout()], Some(wgpu::BlendState::ALPHA_BLENDING))?;
+
+        let mut renderer = Self {
+            _instance: instance,
             surface,
-            adapter,
+            _adapter: adapter,
             device,
             queue,
             config,
 
-            pipeline: render_pipeline,
+            fractals,
+            fractal,
+            parameters,
+            coloring,
 
-            shader,
+            iterations,
+            needs_iteration: true,
+            preview_needs_iteration: true,
+            coloring_pipeline,
+            _coloring_shader: coloring_shader,
+            histogram_clear_pipeline,
+            histogram_range_pipeline,
+            histogram_count_pipeline,
+            histogram_prefix_pipeline,
+            _histogram_shader: histogram_shader,
+
+            pipelines: HashMap::new(),
+            precision,
+            mode,
+
+            _shaders: HashMap::new(),
             camera,
+            julia_camera,
+            reference,
+            polynomial,
+            sequence,
             vertex_buffer,
-        })
+
+            density,
+            density_pipeline,
+            density_clear_pipeline,
+            _density_shader: density_shader,
+
+            preview_camera,
+            preview_visible: true,
+
+            overlay,
+            overlay_pipeline,
+            _overlay_shader: overlay_shader,
+        };
+        renderer.init_fractal_pipelines()?;
+
+        Ok(renderer)
     }
 
     pub fn render(&mut self) -> std::result::Result<(), wgpu::SurfaceError> {
@@ -82,6 +284,25 @@ impl Renderer {
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
+        if self.get_fractal().resource(self.precision) == Some(Resource::DensityMap) {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Density Compute Pass"),
+            });
+
+            compute_pass.set_bind_group(0, self.camera.get_bind_group(), &[]);
+            compute_pass.set_bind_group(1, self.parameters.get_bind_group(), &[]);
+            compute_pass.set_bind_group(2, self.coloring.get_bind_group(), &[]);
+            compute_pass.set_bind_group(3, self.density.get_compute_bind_group(), &[]);
+            if self.density.is_first_frame() {
+                compute_pass.set_pipeline(&self.density_clear_pipeline);
+                compute_pass.dispatch(DensityMap::CLEAR_WORKGROUPS, 1, 1);
+            }
+            compute_pass.set_pipeline(&self.density_pipeline);
+            compute_pass.dispatch(DensityMap::WORKGROUPS, 1, 1);
+        }
+        if self.get_fractal().uses_palette() {
+            self.iterate(&mut encoder);
+        }
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
@@ -101,10 +322,42 @@ impl Renderer {
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, self.camera.get_bind_group(), &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..Self::VERTICES.len() as u32, 0..1);
+            if self.get_fractal().uses_palette() {
+                render_pass.set_pipeline(&self.coloring_pipeline);
+                render_pass.set_bind_group(0, self.active_camera().get_bind_group(), &[]);
+                render_pass.set_bind_group(1, self.parameters.get_bind_group(), &[]);
+                render_pass.set_bind_group(2, self.coloring.get_bind_group(), &[]);
+                render_pass.set_bind_group(3, self.iterations.get_bind_group(), &[]);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.draw(0..Self::VERTICES.len() as u32, 0..1);
+            }
+            else {
+                self.draw_fractal(&mut render_pass, (self.mode, self.precision), self.active_camera());
+            }
+
+            if self.overlay.is_visible() {
+                render_pass.set_pipeline(&self.overlay_pipeline);
+                render_pass.set_bind_group(0, self.overlay.get_bind_group(), &[]);
+                render_pass.draw(0..Self::VERTICES.len() as u32, 0..1);
+            }
+        }
+        if self.is_preview_shown() && !self.get_fractal().uses_palette() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Preview Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            let (x, y, size) = Self::preview_rect(self.config.width as f64, self.config.height as f64);
+            render_pass.set_viewport(x as f32, y as f32, size as f32, size as f32, 0.0, 1.0);
+            self.draw_fractal(&mut render_pass, (Mode::Julia, Precision::Single), &self.preview_camera);
         }
     
         // submit will accept anything that implements IntoIter
@@ -114,25 +367,517 @@ impl Renderer {
         Ok(())
     }
     
+    fn iterate(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        //! Render the current fractal's escape-time results into
+        //! [`Iterations`], if the view has changed since they were last
+        //! rendered, and their histogram if it's needed for coloring. The
+        //! Julia preview goes on top, in its corner, after the histogram is
+        //! built; its corner is masked out of the histogram too
+
+        if self.needs_iteration {
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Iteration Pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: self.iterations.get_view(),
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+
+                self.draw_fractal(&mut render_pass, (self.mode, self.precision), self.active_camera());
+            }
+            if self.coloring.get_mode() == ColoringMode::Histogram {
+                self.iterations.set_histogram_mask(self.preview_mask(), &self.queue);
+                self.build_histogram(encoder);
+            }
+            // that covered the preview's corner too
+            self.needs_iteration = false;
+            self.preview_needs_iteration = true;
+        }
+        if self.is_preview_shown() && self.preview_needs_iteration {
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Preview Iteration Pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: self.iterations.get_view(),
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+
+                let (x, y, size) = Self::preview_rect(self.config.width as f64, self.config.height as f64);
+                render_pass.set_viewport(x as f32, y as f32, size as f32, size as f32, 0.0, 1.0);
+                self.draw_fractal(&mut render_pass, (Mode::Julia, Precision::Single), &self.preview_camera);
+            }
+            self.preview_needs_iteration = false;
+        }
+    }
+
+    fn preview_mask(&self) -> HistogramMask {
+        //! The pixels the Julia preview covers, if it's shown, so that they
+        //! can be left out of the histogram; the histogram is built before
+        //! the preview is drawn, but the pixels under it aren't seen
+
+        if !self.is_preview_shown() {
+            return HistogramMask::default();
+        }
+        let (x, y, size) = Self::preview_rect(self.config.width as f64, self.config.height as f64);
+        HistogramMask {
+            x: x as u32,
+            y: y as u32,
+            width: size as u32,
+            height: size as u32,
+        }
+    }
+
+    fn build_histogram(&self, encoder: &mut wgpu::CommandEncoder) {
+        //! Build the histogram of the escape-time results, and add it up for
+        //! the coloring pass
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Histogram Compute Pass"),
+        });
+
+        compute_pass.set_bind_group(0, self.active_camera().get_bind_group(), &[]);
+        compute_pass.set_bind_group(1, self.parameters.get_bind_group(), &[]);
+        compute_pass.set_bind_group(2, self.coloring.get_bind_group(), &[]);
+        compute_pass.set_bind_group(3, self.iterations.get_compute_bind_group(), &[]);
+        let (x, y) = self.iterations.get_histogram_workgroups();
+        compute_pass.set_pipeline(&self.histogram_clear_pipeline);
+        compute_pass.dispatch(1, 1, 1);
+        compute_pass.set_pipeline(&self.histogram_range_pipeline);
+        compute_pass.dispatch(x, y, 1);
+        compute_pass.set_pipeline(&self.histogram_count_pipeline);
+        compute_pass.dispatch(x, y, 1);
+        compute_pass.set_pipeline(&self.histogram_prefix_pipeline);
+        compute_pass.dispatch(1, 1, 1);
+    }
+
+    fn draw_fractal<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, key: PipelineKey, camera: &'a Camera) {
+        //! Draw the current fractal with the pipeline for @key, as seen by
+        //! @camera
+
+        render_pass.set_pipeline(&self.pipelines[&key]);
+        render_pass.set_bind_group(0, camera.get_bind_group(), &[]);
+        render_pass.set_bind_group(1, self.parameters.get_bind_group(), &[]);
+        render_pass.set_bind_group(2, self.coloring.get_bind_group(), &[]);
+        if let Some(resource) = self.get_fractal().resource(key.1) {
+            render_pass.set_bind_group(3, self.get_resource_bind_group(resource), &[]);
+        }
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..Self::VERTICES.len() as u32, 0..1);
+    }
+
     pub fn update(&mut self, dt: &Duration) -> Result<()> {
-        self.camera.update(dt, &self.queue);
+        let moved = match self.mode {
+            Mode::Mandelbrot => self.camera.update(dt, &self.queue),
+            Mode::Julia => self.julia_camera.update(dt, &self.queue),
+        };
+
+        self.parameters.advance(dt.as_secs_f64());
+        let changed = self.parameters.update(&self.queue);
+        self.coloring.advance(dt.as_secs_f64());
+        let rescaled = self.coloring.update(&self.queue);
+        if moved || changed || rescaled {
+            self.needs_iteration = true;
+        }
+
+        let precision = Self::precision_for_zoom(self.get_fractal(), self.mode, self.active_camera().get_zoom());
+        if precision != self.precision {
+            log::info!("switching to {:?} precision", precision);
+            self.precision = precision;
+            self.needs_iteration = true;
+        }
+        match self.get_fractal().resource(self.precision) {
+            Some(Resource::ReferenceOrbit) => {
+                // a new orbit finishing changes the picture too
+                self.needs_iteration |= self.reference.update(&self.camera, &self.queue);
+            },
+            Some(Resource::Polynomial) => self.polynomial.update(&self.queue),
+            Some(Resource::Sequence) => self.sequence.update(&self.queue),
+            Some(Resource::DensityMap) => {
+                if moved || changed {
+                    self.density.clear();
+                }
+                self.density.update(&self.queue);
+            },
+            None => {},
+        }
+        self.overlay.update(self.active_camera().get_selection(), &self.queue);
+        if self.is_preview_shown() {
+            self.preview_camera.set_julia_c(self.camera.get_cursor_point());
+            if self.preview_camera.update(dt, &self.queue) {
+                self.preview_needs_iteration = true;
+            }
+        }
 
         Ok(())
     }
 
     pub fn input(&mut self, window: &winit::window::Window, event: &winit::event::WindowEvent) -> bool {
-        let mut done: bool;
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::J),
+                ..
+            },
+            ..
+        } = event {
+            self.toggle_julia();
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::F),
+                ..
+            },
+            ..
+        } = event {
+            let next = (self.fractal + 1) % self.fractals.len();
+            if let Err(e) = self.set_fractal(next) {
+                eprintln!("{:?}", e);
+            }
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::P),
+                ..
+            },
+            ..
+        } = event {
+            self.preview_visible = !self.preview_visible;
+            self.needs_iteration = true;
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::C),
+                ..
+            },
+            ..
+        } = event {
+            self.coloring.toggle_palette_cycle();
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::M),
+                ..
+            },
+            ..
+        } = event {
+            let mode = self.coloring.get_mode().next();
+            self.coloring.set_mode(mode);
+            log::info!("switching to {} coloring", mode.name());
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::I),
+                ..
+            },
+            ..
+        } = event {
+            let mode = self.coloring.get_interior_mode().next();
+            self.coloring.set_interior_mode(mode);
+            log::info!("switching to {} interior coloring", mode.name());
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::T),
+                ..
+            },
+            ..
+        } = event {
+            let trap = self.coloring.get_trap_mut();
+            let shape = trap.get_shape().next();
+            trap.set_shape(shape);
+            log::info!("switching to {} orbit trap", shape.name());
+            return true;
+        }
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::G),
+                ..
+            },
+            ..
+        } = event {
+            let center = self.active_camera().get_cursor_point();
+            self.coloring.get_trap_mut().set_center(center);
+            log::info!("moving orbit trap to {} + {}i", center.re, center.im);
+            return true;
+        }
+
+        // keep the inactive camera's cursor up to date, so it's ready to
+        // pick up from the active one
+        if let WindowEvent::CursorMoved { .. } | WindowEvent::ModifiersChanged(_) = event {
+            self.inactive_camera_mut().input(window, event);
+        }
 
-        done = self.camera.input(window, event);
+        let done: bool = self.active_camera_mut().input(window, event);
 
         done
     }
 
+    pub fn toggle_julia(&mut self) {
+        //! From the Mandelbrot set, switch to the Julia set for the point
+        //! under the cursor. From a Julia set, switch back to the Mandelbrot
+        //! set. Each keeps its own camera, so neither loses its place
+
+        match self.mode {
+            Mode::Mandelbrot if !self.get_fractal().has_julia() => {
+                log::warn!("{} has no Julia sets", self.get_fractal().name());
+            },
+            Mode::Mandelbrot => {
+                let c = self.camera.get_cursor_point();
+                self.julia_camera.set_julia_c(c);
+                self.mode = Mode::Julia;
+                log::info!("switching to Julia set for c = {} + {}i", c.re, c.im);
+            },
+            Mode::Julia => {
+                self.mode = Mode::Mandelbrot;
+                log::info!("switching to Mandelbrot set");
+            },
+        }
+        self.needs_iteration = true;
+    }
+
+    fn is_preview_shown(&self) -> bool {
+        //! The Julia preview only makes sense over the Mandelbrot set, where
+        //! the cursor is pointing at a `c`
+
+        self.preview_visible && self.mode == Mode::Mandelbrot && self.get_fractal().has_julia()
+    }
+
+    pub fn set_fractal(&mut self, index: usize) -> Result<()> {
+        //! Switch to the fractal at @index in the registry, rebuilding the
+        //! pipelines for its shaders, and resetting the cameras and
+        //! parameters to its defaults
+
+        self.fractal = index;
+        self.init_fractal_pipelines()?;
+
+        let fractal = self.fractals[index].as_ref();
+        self.camera.reset(fractal.default_scale(), fractal.default_origin());
+        self.julia_camera.reset(fractal.julia_scale(), fractal.julia_origin());
+        self.preview_camera.reset(fractal.julia_scale(), fractal.julia_origin());
+        self.parameters.reset(fractal.parameters());
+        self.mode = Mode::Mandelbrot;
+        self.precision = Self::precision_for_zoom(fractal, self.mode, self.camera.get_zoom());
+        self.needs_iteration = true;
+        log::info!("switching to {}", fractal.name());
+
+        Ok(())
+    }
+
+    pub fn set_parameter(&mut self, name: &str, value: f32) -> Result<()> {
+        //! Set the current fractal's parameter called @name to @value
+
+        self.parameters.set(name, value)
+    }
+
+    pub fn set_bailout(&mut self, radius: f32) -> Result<()> {
+        //! Use an escape radius of @radius for escape-time fractals
+
+        self.coloring.set_bailout(radius)
+    }
+
+    pub fn load_palette(&mut self, path: &Path) -> Result<()> {
+        //! Color escape-time fractals with the gradient file at @path (see
+        //! [`Palette::load`])
+
+        self.coloring.set_palette(Palette::load(path)?);
+        log::info!("loaded palette {}", path.display());
+
+        Ok(())
+    }
+
+    pub fn set_palette_offset(&mut self, offset: f32) {
+        self.coloring.set_palette_offset(offset);
+    }
+
+    pub fn set_palette_repeat(&mut self, repeat: f32) -> Result<()> {
+        self.coloring.set_palette_repeat(repeat)
+    }
+
+    pub fn set_palette_mirror(&mut self, mirror: bool) {
+        self.coloring.set_palette_mirror(mirror);
+    }
+
+    pub fn set_coloring_mode(&mut self, name: &str) -> Result<()> {
+        //! Color escape-time fractals with the [`ColoringMode`] called @name
+
+        self.coloring.set_mode(ColoringMode::from_name(name)?);
+
+        Ok(())
+    }
+
+    pub fn set_interior_mode(&mut self, name: &str) -> Result<()> {
+        //! Color the insides of escape-time fractals with the
+        //! [`InteriorMode`] called @name
+
+        self.coloring.set_interior_mode(InteriorMode::from_name(name)?);
+
+        Ok(())
+    }
+
+    pub fn set_trap_shape(&mut self, name: &str) -> Result<()> {
+        //! Use the [`TrapShape`] called @name for the orbit trap
+
+        self.coloring.get_trap_mut().set_shape(TrapShape::from_name(name)?);
+
+        Ok(())
+    }
+
+    pub fn set_trap_center(&mut self, center: Complex) {
+        self.coloring.get_trap_mut().set_center(center);
+    }
+
+    pub fn set_trap_rotation(&mut self, degrees: f64) {
+        self.coloring.get_trap_mut().set_rotation(degrees);
+    }
+
+    pub fn set_trap_size(&mut self, size: f32) -> Result<()> {
+        self.coloring.get_trap_mut().set_size(size)
+    }
+
+    pub fn load_trap_image(&mut self, path: &Path) -> Result<()> {
+        //! Use the image file at @path as the orbit trap
+
+        self.coloring.get_trap_mut().load_image(path)?;
+        log::info!("loaded orbit trap image {}", path.display());
+
+        Ok(())
+    }
+
+    pub fn set_line_width(&mut self, width: f32) -> Result<()> {
+        self.coloring.set_line_width(width)
+    }
+
+    pub fn set_stripe_density(&mut self, density: f32) -> Result<()> {
+        self.coloring.set_stripe_density(density)
+    }
+
+    pub fn set_palette_cycle(&mut self, speed: f32) -> Result<()> {
+        //! Cycle the palette by @speed palette lengths per second
+
+        self.coloring.set_palette_cycle(speed)
+    }
+
+    pub fn set_polynomial_roots(&mut self, roots: &[Complex]) -> Result<()> {
+        //! Use the polynomial with @roots for Newton's method fractals
+
+        self.polynomial.set_roots(roots)
+    }
+
+    pub fn set_polynomial_coefficients(&mut self, coefficients: &[Complex]) -> Result<()> {
+        //! Use the polynomial with @coefficients (lowest order first) for
+        //! Newton's method fractals
+
+        self.polynomial.set_coefficients(coefficients)
+    }
+
+    pub fn set_lyapunov_sequence(&mut self, sequence: &str) -> Result<()> {
+        //! Use @sequence (e.g. `"AABAB"`) for Lyapunov fractals
+
+        self.sequence.set(sequence)?;
+        log::info!("lyapunov sequence: {}", self.sequence.get_text());
+
+        Ok(())
+    }
+
+    pub fn animate_parameter(&mut self, name: &str, from: f32, to: f32, duration: f64) -> Result<()> {
+        //! Sweep the current fractal's parameter called @name back and forth
+        //! between @from and @to, taking @duration seconds each way
+
+        self.parameters.animate(name, from, to, duration)
+    }
+
+    pub fn get_fractal(&self) -> &dyn Fractal {
+        self.fractals[self.fractal].as_ref()
+    }
+
+    fn find_fractal(fractals: &[Box<dyn Fractal>], name: &str) -> Result<usize> {
+        fractals.iter()
+            .position(|fractal| fractal.name() == name)
+            .ok_or_else(|| anyhow!("unknown fractal {}", name))
+    }
+
+    fn precision_for_zoom(fractal: &dyn Fractal, mode: Mode, zoom: f64) -> Precision {
+        //! Like [Precision::for_zoom], but limited to the precisions that
+        //! @fractal has shaders for in @mode. Past its most precise shader,
+        //! the fractal just gets blocky
+
+        let wanted = Precision::for_zoom(zoom);
+        Precision::ALL.iter()
+            .take_while(|precision| **precision != wanted)
+            .chain(std::iter::once(&wanted))
+            .filter(|precision| fractal.shader_path(mode, **precision).is_some())
+            .last()
+            .copied()
+            .unwrap_or(Precision::Single)
+    }
+
+    fn preview_rect(width: f64, height: f64) -> (f64, f64, f64) {
+        //! Where the Julia preview inset goes in a @width x @height window;
+        //! a square in the bottom-right corner, as (x, y, size) in pixels
+
+        let size = (width.min(height) * Self::PREVIEW_SIZE).floor().max(1.0);
+        let x = (width - size - Self::PREVIEW_MARGIN).max(0.0);
+        let y = (height - size - Self::PREVIEW_MARGIN).max(0.0);
+
+        (x, y, size)
+    }
+
+    fn active_camera(&self) -> &Camera {
+        match self.mode {
+            Mode::Mandelbrot => &self.camera,
+            Mode::Julia => &self.julia_camera,
+        }
+    }
+
+    fn active_camera_mut(&mut self) -> &mut Camera {
+        match self.mode {
+            Mode::Mandelbrot => &mut self.camera,
+            Mode::Julia => &mut self.julia_camera,
+        }
+    }
+
+    fn inactive_camera_mut(&mut self) -> &mut Camera {
+        match self.mode {
+            Mode::Mandelbrot => &mut self.julia_camera,
+            Mode::Julia => &mut self.camera,
+        }
+    }
+
+    pub fn set_zoom_sensitivity(&mut self, sensitivity: f64) {
+        self.camera.set_zoom_sensitivity(sensitivity);
+        self.julia_camera.set_zoom_sensitivity(sensitivity);
+    }
+
     pub fn resize(&mut self, mut size: winit::dpi::PhysicalSize<u32>) {
-        if size.width <= 0 {
+        if size.width == 0 {
             size.width = 1;
         }
-        if size.height <= 0 {
+        if size.height == 0 {
             size.height = 1;
         }
 
@@ -140,12 +885,19 @@ impl Renderer {
         self.config.height = size.height;
         self.surface.configure(&self.device, &self.config);
         self.camera.resize(size.width, size.height);
+        self.julia_camera.resize(size.width, size.height);
+        self.density.resize(&self.device, size.width, size.height);
+        self.iterations.resize(&self.device, size.width, size.height);
+        self.needs_iteration = true;
+
+        let (preview_x, preview_y, preview_size) = Self::preview_rect(size.width as f64, size.height as f64);
+        self.preview_camera.set_viewport(preview_x, preview_y, preview_size, preview_size);
     }
 
     async fn init_device(window: &winit::window::Window) -> Result<(wgpu::Instance, wgpu::Surface, wgpu::Adapter, wgpu::Device, wgpu::Queue, wgpu::SurfaceConfiguration)> {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::Backends::all());
-        let surface = unsafe { instance.create_surface(window) };
+        let surface = unsafe { instance.create_surface(&WindowHandle(window)) };
         let adapter = instance.request_adapter(
             &wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::default(),
@@ -193,13 +945,66 @@ impl Renderer {
         Ok(buffer)
     }
 
-    fn init_pipeline(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, shader: &Shader, camera: &Camera) -> Result<wgpu::RenderPipeline> {
+    fn init_fractal_pipelines(&mut self) -> Result<()> {
+        //! Build a pipeline for each of the current fractal's shaders. Every
+        //! camera has the same layout, so the main camera's is used for all
+        //! of them
+
+        let fractal = self.fractals[self.fractal].as_ref();
+        let mut shaders = HashMap::new();
+        let mut pipelines = HashMap::new();
+        for mode in Mode::ALL {
+            for precision in Precision::ALL {
+                let shader_path = match fractal.shader_path(mode, precision) {
+                    Some(shader_path) => shader_path,
+                    None => continue,
+                };
+                let shader = Shader::new(&self.device, shader_path)?;
+                let mut layouts = vec![self.camera.get_layout(), self.parameters.get_layout(), self.coloring.get_layout()];
+                if let Some(resource) = fractal.resource(precision) {
+                    layouts.push(self.get_resource_layout(resource));
+                }
+                // escape-time results can't be blended, so they're just
+                // written as they are
+                let (format, blend) = if fractal.uses_palette() {
+                    (Iterations::FORMAT, None)
+                }
+                else {
+                    (self.config.format, Some(wgpu::BlendState::REPLACE))
+                };
+                pipelines.insert((mode, precision), Self::init_pipeline(&self.device, format, &shader, fractal.entry_point(mode), &layouts, blend)?);
+                shaders.insert((mode, precision), shader);
+            }
+        }
+        self._shaders = shaders;
+        self.pipelines = pipelines;
+
+        Ok(())
+    }
+
+    fn get_resource_layout(&self, resource: Resource) -> &wgpu::BindGroupLayout {
+        match resource {
+            Resource::ReferenceOrbit => self.reference.get_layout(),
+            Resource::Polynomial => self.polynomial.get_layout(),
+            Resource::Sequence => self.sequence.get_layout(),
+            Resource::DensityMap => self.density.get_layout(),
+        }
+    }
+
+    fn get_resource_bind_group(&self, resource: Resource) -> &wgpu::BindGroup {
+        match resource {
+            Resource::ReferenceOrbit => self.reference.get_bind_group(),
+            Resource::Polynomial => self.polynomial.get_bind_group(),
+            Resource::Sequence => self.sequence.get_bind_group(),
+            Resource::DensityMap => self.density.get_bind_group(),
+        }
+    }
+
+    fn init_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat, shader: &Shader, entry_point: &str, bind_group_layouts: &[&wgpu::BindGroupLayout], blend: Option<wgpu::BlendState>) -> Result<wgpu::RenderPipeline> {
         let layout = device.create_pipeline_layout(
             &wgpu::PipelineLayoutDescriptor {
                 label: Some("render_pipline_layout"),
-                bind_group_layouts: &[
-                    camera.get_layout()
-                ],
+                bind_group_layouts,
                 push_constant_ranges: &[],
             }
         );
@@ -216,10 +1021,10 @@ impl Renderer {
                 },
                 fragment: Some(wgpu::FragmentState {
                     module: shader.get_module(),
-                    entry_point: "fs_main",
+                    entry_point,
                     targets: &[wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
+                        format,
+                        blend,
                         write_mask: wgpu::ColorWrites::ALL,
                     }],
                 }),
@@ -245,5 +1050,23 @@ impl Renderer {
         Ok(pipeline)
     }
 
+    fn init_compute_pipeline(device: &wgpu::Device, shader: &Shader, entry_point: &str, bind_group_layouts: &[&wgpu::BindGroupLayout]) -> Result<wgpu::ComputePipeline> {
+        let layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: Some("compute_pipline_layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            }
+        );
+        let pipeline = device.create_compute_pipeline(
+            &wgpu::ComputePipelineDescriptor {
+                label: Some("compute_pipeline"),
+                layout: Some(&layout),
+                module: shader.get_module(),
+                entry_point,
+            }
+        );
 
+        Ok(pipeline)
+    }
 }
@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Result};
+use bytemuck;
+use wgpu;
+use wgpu::util::DeviceExt;
+
+/// The GPU-side view of [`Sequence`]; the steps themselves are in a storage
+/// buffer
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct SequenceUniform {
+    /// Number of steps in the steps buffer
+    length: u32,
+
+    _padding: [u32; 3],
+}
+
+/// The `A`/`B` sequence for Lyapunov fractals, e.g. `"AABAB"`. Each step
+/// picks which of the pixel's two coordinates (`a` or `b`) drives the
+/// logistic map next; the sequence repeats for as long as the map runs
+#[derive(Debug)]
+pub struct Sequence {
+    /// The sequence as given, and as uploaded: 0 for `A`, 1 for `B`
+    text: String,
+    steps: Vec<u32>,
+
+    /// Data uploaded to the GPU
+    uniform: SequenceUniform,
+    needs_upload: bool,
+
+    /// WGPU objects
+    uniform_buffer: wgpu::Buffer,
+    steps_buffer: wgpu::Buffer,
+    layout: wgpu::BindGroupLayout,
+    group: wgpu::BindGroup,
+}
+
+impl Sequence {
+    /// Longest sequence that fits in the steps buffer
+    pub const MAX_LENGTH: usize = 64;
+
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = SequenceUniform {
+            length: 0,
+            _padding: [0; 3],
+        };
+        let uniform_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("sequence_uniform_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        let steps_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sequence_steps_buffer"),
+            size: Self::MAX_LENGTH as u64 * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("sequence_bind_group_layout"),
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: steps_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("sequence_bind_group"),
+        });
+
+        let mut sequence = Self {
+            text: String::new(),
+            steps: Vec::new(),
+            uniform,
+            needs_upload: false,
+            uniform_buffer,
+            steps_buffer,
+            layout,
+            group,
+        };
+        sequence.set("AB")?;
+
+        Ok(sequence)
+    }
+
+    pub fn set(&mut self, text: &str) -> Result<()> {
+        //! Use the sequence in @text; a string of `A`s and `B`s (in either
+        //! case)
+
+        let steps = text.chars()
+            .map(|step| match step.to_ascii_uppercase() {
+                'A' => Ok(0),
+                'B' => Ok(1),
+                _ => Err(anyhow!("invalid step {:?} in sequence {}; only A and B are allowed", step, text)),
+            })
+            .collect::<Result<Vec<u32>>>()?;
+        if steps.is_empty() || steps.len() > Self::MAX_LENGTH {
+            return Err(anyhow!("a sequence needs between 1 and {} steps", Self::MAX_LENGTH));
+        }
+
+        self.text = text.to_ascii_uppercase();
+        self.steps = steps;
+        self.uniform.length = self.steps.len() as u32;
+        self.needs_upload = true;
+
+        Ok(())
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) {
+        if self.needs_upload {
+            queue.write_buffer(&self.steps_buffer, 0, bytemuck::cast_slice(&self.steps));
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            self.needs_upload = false;
+        }
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn get_bind_group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+}
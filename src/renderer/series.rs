@@ -0,0 +1,172 @@
+use super::complex::Complex;
+
+/// Series approximation, for skipping the start of each pixel's iteration
+/// when rendering with perturbation
+///
+/// Near the reference orbit, each pixel's offset `dz_n` is well approximated
+/// by a polynomial in its offset `dc` from the reference point:
+///
+/// `dz_n ~= A_1 * dc + A_2 * dc^2 + ... + A_k * dc^k`
+///
+/// The coefficients only depend on the reference orbit, so they are found
+/// once on the CPU, and the shader evaluates the polynomial to jump straight
+/// to iteration `n`.
+///
+/// The coefficients are stored pre-scaled by the radius `r` of the region
+/// they must cover (`A_k * r^k`), and evaluated against `dc / r`. This keeps
+/// them within the range of `f32`, even at deep zooms where `A_k` is huge and
+/// `r` is tiny
+#[derive(Debug, Clone)]
+pub struct SeriesApproximation {
+    /// Scaled coefficients `A_k * r^k`, lowest order first
+    coefficients: Vec<Complex>,
+
+    /// Radius of the region the approximation is valid for
+    radius: f64,
+
+    /// Number of iterations that can be skipped
+    skip: u32,
+}
+
+impl SeriesApproximation {
+    /// Number of terms in the polynomial
+    pub const TERMS: usize = 6;
+
+    /// How small the first neglected term must be, relative to the linear
+    /// term, for the approximation to count as accurate. Anything below the
+    /// precision of `f32` is invisible to the shader anyway
+    pub const TOLERANCE: f64 = 1.0 / (1 << 24) as f64;
+
+    pub fn new(orbit: &[Complex], radius: f64) -> Self {
+        //! Find coefficients for the reference @orbit, covering any point
+        //! within @radius of the reference point, and skipping as many
+        //! iterations as possible
+        //!
+        //! One extra term beyond [Self::TERMS] is tracked, as an estimate of
+        //! the truncation error. Iteration stops as soon as that term is no
+        //! longer negligible
+
+        let mut terms = vec![Complex::new(0.0, 0.0); Self::TERMS + 1];
+        let mut coefficients = terms[..Self::TERMS].to_vec();
+        let mut skip = 0;
+
+        // the orbit must continue past the skipped iterations, so the shader
+        // can pick up from there
+        let max_skip = orbit.len().saturating_sub(2);
+        for (n, z) in orbit.iter().enumerate().take(max_skip) {
+            terms = Self::step(&terms, *z, radius);
+
+            let linear = terms[0].norm_sqr().sqrt();
+            let error = terms[Self::TERMS].norm_sqr().sqrt();
+            if error > Self::TOLERANCE * linear || !error.is_finite() {
+                break;
+            }
+
+            coefficients.copy_from_slice(&terms[..Self::TERMS]);
+            skip = n as u32 + 1;
+        }
+
+        Self {
+            coefficients,
+            radius,
+            skip,
+        }
+    }
+
+    pub fn get_coefficients(&self) -> &[Complex] {
+        &self.coefficients
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn get_skip(&self) -> u32 {
+        self.skip
+    }
+
+    fn step(terms: &[Complex], z: Complex, radius: f64) -> Vec<Complex> {
+        //! Advance the (scaled) coefficients by one iteration, from
+        //! `dz_{n+1} = 2 * Z_n * dz_n + dz_n^2 + dc`:
+        //!
+        //! `A_1 -> 2 * Z_n * A_1 + 1`
+        //! `A_k -> 2 * Z_n * A_k + sum(A_j * A_{k-j}, j = 1..k-1)`
+
+        let two_z = z + z;
+        (0..terms.len())
+            .map(|k| {
+                let mut next = two_z * terms[k];
+                for j in 0..k {
+                    next = next + terms[j] * terms[k - 1 - j];
+                }
+                if k == 0 {
+                    next = next + Complex::new(radius, 0.0);
+                }
+                next
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference_orbit(c: Complex, length: usize) -> Vec<Complex> {
+        let mut z = Complex::new(0.0, 0.0);
+        let mut orbit = vec![z];
+        for _ in 1..length {
+            z = z * z + c;
+            orbit.push(z);
+        }
+        orbit
+    }
+
+    fn evaluate(series: &SeriesApproximation, dc: Complex) -> Complex {
+        //! The series at @dc, as the shader evaluates it
+
+        let scale = 1.0 / series.get_radius();
+        let u = Complex::new(dc.re * scale, dc.im * scale);
+        series.get_coefficients().iter().rev()
+            .fold(Complex::new(0.0, 0.0), |res, a| (res + *a) * u)
+    }
+
+    #[test]
+    fn matches_direct_iteration() {
+        // inside the main cardioid, so the orbit never escapes
+        let c = Complex::new(-0.1, 0.2);
+        let orbit = reference_orbit(c, 200);
+        let series = SeriesApproximation::new(&orbit, 1e-6);
+        let skip = series.get_skip() as usize;
+        assert!(skip > 0);
+
+        let dc = Complex::new(3e-7, -4e-7);
+        let mut z = Complex::new(0.0, 0.0);
+        for _ in 0..skip {
+            z = z * z + c + dc;
+        }
+        let expected = z - orbit[skip];
+        let actual = evaluate(&series, dc);
+        let error = (actual - expected).norm_sqr().sqrt();
+        assert!(error <= 1e-6 * expected.norm_sqr().sqrt(), "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn first_step_is_linear() {
+        // after one iteration, `dz_1 = dc` exactly
+        let orbit = reference_orbit(Complex::new(-0.1, 0.2), 3);
+        let terms = vec![Complex::new(0.0, 0.0); SeriesApproximation::TERMS + 1];
+        let terms = SeriesApproximation::step(&terms, orbit[0], 0.5);
+        assert_eq!((terms[0].re, terms[0].im), (0.5, 0.0));
+        assert!(terms[1..].iter().all(|a| a.norm_sqr() == 0.0));
+    }
+
+    #[test]
+    fn leaves_the_end_of_the_orbit() {
+        // the shader has to pick up after the skipped iterations, so at
+        // least one point must be left
+        let orbit = reference_orbit(Complex::new(-0.1, 0.2), 5);
+        let series = SeriesApproximation::new(&orbit, 1e-9);
+        assert!((series.get_skip() as usize) < orbit.len() - 1);
+    }
+}
@@ -1,25 +1,21 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use wgpu;
 
 pub struct Shader {
-    /// Path to the shader file
-    path: PathBuf,
-    
     /// Shader module to be passed to RenderPipeline
     module: wgpu::ShaderModule,
 }
 
 impl Shader {
-    pub fn new<T: Clone>(device: &wgpu::Device, path: T) -> Result<Self> where T: AsRef<Path> {
-        let source = std::fs::read_to_string(path.clone())?;
+    pub fn new<T>(device: &wgpu::Device, path: T) -> Result<Self> where T: AsRef<Path> + Clone {
+        let source = Self::load(path.as_ref(), &mut Vec::new())?;
         let descriptor = wgpu::ShaderModuleDescriptor {
             label: Some("fractal_shader"),
             source: wgpu::ShaderSource::Wgsl(source.into()),
         };
 
         Ok(Self {
-            path: path.as_ref().to_path_buf(),
             module: device.create_shader_module(&descriptor),
         })
     }
@@ -27,4 +23,37 @@ impl Shader {
     pub fn get_module(&self) -> &wgpu::ShaderModule {
         &self.module
     }
+
+    fn load(path: &Path, included: &mut Vec<PathBuf>) -> Result<String> {
+        //! Read the shader at @path, replacing any `#include "file.wgsl"`
+        //! lines with the contents of that file (relative to @path)
+        //!
+        //! WGSL has no module system of its own, so this is how the shaders
+        //! share their helper functions. Each file is only included once;
+        //! @included tracks the files that have been pulled in so far
+
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read shader {}", path.display()))?;
+        included.push(path.to_path_buf());
+
+        let mut output = String::with_capacity(source.len());
+        for line in source.lines() {
+            let include = line.trim()
+                .strip_prefix("#include")
+                .map(|rest| rest.trim().trim_matches('"'));
+
+            match include {
+                Some(name) => {
+                    let include_path = path.parent().unwrap_or_else(|| Path::new("")).join(name);
+                    if !included.contains(&include_path) {
+                        output.push_str(&Self::load(&include_path, included)?);
+                    }
+                },
+                None => output.push_str(line),
+            }
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
 }
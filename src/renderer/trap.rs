@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Context, Result};
+use bytemuck;
+use image::imageops::FilterType;
+use std::path::Path;
+use wgpu;
+use wgpu::util::DeviceExt;
+use super::Complex;
+
+/// The GPU-side view of [`OrbitTrap`]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct TrapUniform {
+    /// See [`TrapShape`]
+    shape: u32,
+
+    /// See [`OrbitTrap::set_size`]
+    size: f32,
+
+    /// Where the trap is, on the complex plane
+    center_re: f32,
+    center_im: f32,
+
+    /// The trap's rotation; uploaded pre-calculated, since every iteration
+    /// of every pixel needs them
+    rotation_cos: f32,
+    rotation_sin: f32,
+
+    _padding: [u32; 2],
+}
+
+/// The shape orbits are measured against, in [`super::ColoringMode::OrbitTrap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrapShape {
+    /// A single point, at the trap's center
+    Point,
+
+    /// A line through the center, along the trap's rotation
+    Line,
+
+    /// Two perpendicular lines through the center
+    Cross,
+
+    /// A circle around the center, with the trap's size as its radius
+    Circle,
+
+    /// An image, centered on the trap and spanning its size each way. Rather
+    /// than the closest approach, pixels take the color of the image where
+    /// their orbit first lands on it (see [`OrbitTrap::load_image`])
+    Image,
+}
+
+impl TrapShape {
+    pub const ALL: [TrapShape; 5] = [TrapShape::Point, TrapShape::Line, TrapShape::Cross, TrapShape::Circle, TrapShape::Image];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrapShape::Point => "point",
+            TrapShape::Line => "line",
+            TrapShape::Cross => "cross",
+            TrapShape::Circle => "circle",
+            TrapShape::Image => "image",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL.iter()
+            .find(|shape| shape.name() == name)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown orbit trap shape {}", name))
+    }
+
+    pub fn next(&self) -> Self {
+        //! The shape after this one, wrapping around
+
+        let index = Self::ALL.iter().position(|shape| shape == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// An orbit trap; a shape on the complex plane that pixels are colored by
+/// the closest approach of their orbit to. Shared by every fractal and
+/// camera, and bound alongside [`super::Coloring`] at group 2
+///
+/// The trap is measured during the iteration pass, so any change to it means
+/// iterating again
+#[derive(Debug)]
+pub struct OrbitTrap {
+    /// Data uploaded to the GPU
+    uniform: TrapUniform,
+    needs_upload: bool,
+
+    /// A newly loaded image, waiting to be uploaded
+    image: Option<image::RgbaImage>,
+
+    /// WGPU objects
+    buffer: wgpu::Buffer,
+    image_texture: wgpu::Texture,
+    image_view: wgpu::TextureView,
+}
+
+impl OrbitTrap {
+    /// Size to start with; also the width over which distances from the
+    /// point and line traps run through the palette
+    pub const DEFAULT_SIZE: f32 = 0.5;
+
+    /// Trap images are resized to this many pixels square when loaded
+    pub const IMAGE_SIZE: u32 = 512;
+
+    pub fn new(device: &wgpu::Device) -> Result<Self> {
+        let uniform = TrapUniform {
+            shape: TrapShape::Point as u32,
+            size: Self::DEFAULT_SIZE,
+            center_re: 0.0,
+            center_im: 0.0,
+            rotation_cos: 1.0,
+            rotation_sin: 0.0,
+            _padding: [0; 2],
+        };
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("trap_buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+        // starts out transparent, i.e. never hit
+        let image_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("trap_image_texture"),
+            size: wgpu::Extent3d {
+                width: Self::IMAGE_SIZE,
+                height: Self::IMAGE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        let image_view = image_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Ok(Self {
+            uniform,
+            needs_upload: false,
+            image: None,
+            buffer,
+            image_texture,
+            image_view,
+        })
+    }
+
+    pub fn set_shape(&mut self, shape: TrapShape) {
+        self.uniform.shape = shape as u32;
+        self.needs_upload = true;
+    }
+
+    pub fn get_shape(&self) -> TrapShape {
+        TrapShape::ALL[self.uniform.shape as usize]
+    }
+
+    pub fn set_center(&mut self, center: Complex) {
+        //! Move the trap to @center, on the complex plane
+
+        self.uniform.center_re = center.re as f32;
+        self.uniform.center_im = center.im as f32;
+        self.needs_upload = true;
+    }
+
+    pub fn set_rotation(&mut self, degrees: f64) {
+        //! Turn the trap @degrees anticlockwise; this is the angle of the line
+        //! trap, and of the cross and image traps' horizontal
+
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        self.uniform.rotation_cos = cos as f32;
+        self.uniform.rotation_sin = sin as f32;
+        self.needs_upload = true;
+    }
+
+    pub fn set_size(&mut self, size: f32) -> Result<()> {
+        //! Set the circle trap's radius, and the image trap's half-width, to
+        //! @size. Distances are also measured in units of this, so it sets
+        //! how quickly colors change away from any trap
+
+        if !size.is_finite() || size <= 0.0 {
+            return Err(anyhow!("the orbit trap size must be positive, not {}", size));
+        }
+
+        self.uniform.size = size;
+        self.needs_upload = true;
+
+        Ok(())
+    }
+
+    pub fn load_image(&mut self, path: &Path) -> Result<()> {
+        //! Use the image file at @path as the trap, and switch to the image
+        //! shape. Transparent parts of the image don't trap anything
+
+        let image = image::open(path)
+            .with_context(|| format!("couldn't load orbit trap image {}", path.display()))?;
+        self.image = Some(image.resize_exact(Self::IMAGE_SIZE, Self::IMAGE_SIZE, FilterType::Triangle).to_rgba8());
+        self.set_shape(TrapShape::Image);
+
+        Ok(())
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) -> bool {
+        //! Upload any changes. Returns whether there were any, in which case
+        //! the fractal has to be iterated again
+
+        let changed = self.needs_upload || self.image.is_some();
+        if self.needs_upload {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            self.needs_upload = false;
+        }
+        if let Some(image) = self.image.take() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.image_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                image.as_raw(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(4 * Self::IMAGE_SIZE),
+                    rows_per_image: std::num::NonZeroU32::new(Self::IMAGE_SIZE),
+                },
+                wgpu::Extent3d {
+                    width: Self::IMAGE_SIZE,
+                    height: Self::IMAGE_SIZE,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        changed
+    }
+
+    pub fn get_buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn get_image_view(&self) -> &wgpu::TextureView {
+        &self.image_view
+    }
+}
@@ -0,0 +1,87 @@
+use raw_window_handle as rwh04;
+use raw_window_handle_05 as rwh05;
+use raw_window_handle_05::{HasRawDisplayHandle, HasRawWindowHandle};
+use winit::window::Window;
+
+/// A window, as the older raw window handle that wgpu expects
+///
+/// winit hands out `raw-window-handle` 0.5 handles, with the window and its
+/// display separate, but wgpu 0.12 still takes 0.4 handles, which combine
+/// the two. This wraps a [`Window`] to copy its handles across, so that a
+/// surface can be made for it
+pub struct WindowHandle<'a>(pub &'a Window);
+
+unsafe impl rwh04::HasRawWindowHandle for WindowHandle<'_> {
+    fn raw_window_handle(&self) -> rwh04::RawWindowHandle {
+        //! Panics for kinds of handle winit doesn't make, like DRM or GBM
+
+        match (self.0.raw_window_handle(), self.0.raw_display_handle()) {
+            (rwh05::RawWindowHandle::Xlib(window), rwh05::RawDisplayHandle::Xlib(display)) => {
+                let mut handle = rwh04::XlibHandle::empty();
+                handle.window = window.window;
+                handle.visual_id = window.visual_id;
+                handle.display = display.display;
+                rwh04::RawWindowHandle::Xlib(handle)
+            },
+            (rwh05::RawWindowHandle::Xcb(window), rwh05::RawDisplayHandle::Xcb(display)) => {
+                let mut handle = rwh04::XcbHandle::empty();
+                handle.window = window.window;
+                handle.visual_id = window.visual_id;
+                handle.connection = display.connection;
+                rwh04::RawWindowHandle::Xcb(handle)
+            },
+            (rwh05::RawWindowHandle::Wayland(window), rwh05::RawDisplayHandle::Wayland(display)) => {
+                let mut handle = rwh04::WaylandHandle::empty();
+                handle.surface = window.surface;
+                handle.display = display.display;
+                rwh04::RawWindowHandle::Wayland(handle)
+            },
+            (rwh05::RawWindowHandle::Win32(window), _) => {
+                let mut handle = rwh04::Win32Handle::empty();
+                handle.hwnd = window.hwnd;
+                handle.hinstance = window.hinstance;
+                rwh04::RawWindowHandle::Win32(handle)
+            },
+            (rwh05::RawWindowHandle::WinRt(window), _) => {
+                let mut handle = rwh04::WinRtHandle::empty();
+                handle.core_window = window.core_window;
+                rwh04::RawWindowHandle::WinRt(handle)
+            },
+            (rwh05::RawWindowHandle::AppKit(window), _) => {
+                let mut handle = rwh04::AppKitHandle::empty();
+                handle.ns_window = window.ns_window;
+                handle.ns_view = window.ns_view;
+                rwh04::RawWindowHandle::AppKit(handle)
+            },
+            (rwh05::RawWindowHandle::UiKit(window), _) => {
+                let mut handle = rwh04::UiKitHandle::empty();
+                handle.ui_window = window.ui_window;
+                handle.ui_view = window.ui_view;
+                handle.ui_view_controller = window.ui_view_controller;
+                rwh04::RawWindowHandle::UiKit(handle)
+            },
+            (rwh05::RawWindowHandle::AndroidNdk(window), _) => {
+                let mut handle = rwh04::AndroidNdkHandle::empty();
+                handle.a_native_window = window.a_native_window;
+                rwh04::RawWindowHandle::AndroidNdk(handle)
+            },
+            (rwh05::RawWindowHandle::Web(window), _) => {
+                let mut handle = rwh04::WebHandle::empty();
+                handle.id = window.id;
+                rwh04::RawWindowHandle::Web(handle)
+            },
+            (rwh05::RawWindowHandle::Orbital(window), _) => {
+                let mut handle = rwh04::OrbitalHandle::empty();
+                handle.window = window.window;
+                rwh04::RawWindowHandle::Orbital(handle)
+            },
+            (rwh05::RawWindowHandle::Haiku(window), _) => {
+                let mut handle = rwh04::HaikuHandle::empty();
+                handle.b_window = window.b_window;
+                handle.b_direct_window = window.b_direct_window;
+                rwh04::RawWindowHandle::Haiku(handle)
+            },
+            (window, display) => panic!("unsupported window handle {:?} on display {:?}", window, display),
+        }
+    }
+}